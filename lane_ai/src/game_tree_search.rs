@@ -0,0 +1,585 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::future::Future;
+use lane_logic::{GameOutcome, Move, MoveResult, Player, State};
+
+use crate::transposition::{Bound, Entry, PackedMove, TranspositionTable};
+use crate::Rng;
+use async_recursion::async_recursion;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AIControl {
+    /// How many moves ahead to look, if the budget allows
+    pub depth: u32,
+    pub ai_type: AiControlType,
+    pub budget: Budget,
+    /// How many bytes the table of positions already searched can take up.
+    /// Without one, positions reached by different orders of moves are
+    /// searched again each time.
+    pub table_memory: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AiControlType {
+    Best,
+    WithRandom(i32),
+    Negative,
+}
+
+/// How much searching the AI may do for each move. The search looks one move
+/// ahead, then two and so on up to the AI's depth, and plays the best move
+/// from the deepest search it finished. Looking one move ahead is always
+/// finished, however small the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    Unlimited,
+    /// Stops after making this many moves while searching
+    Nodes(u32),
+    /// Stops once this many frames have passed, going by the search's clock
+    Frames(u32),
+}
+
+impl ScoreCalculator for AiControlType {
+    fn score(&self, result: &MoveResult, node: &State, player: Player, rng: &mut impl Rng) -> i32 {
+        match self {
+            AiControlType::Best => calculate_state_score(result, node, player),
+            AiControlType::WithRandom(random_parameter) => {
+                calculate_state_score(result, node, player)
+                    + rng.next_u32() as i32 % random_parameter
+            }
+            AiControlType::Negative => -calculate_state_score(result, node, player),
+        }
+    }
+}
+
+/// How good the state after a move is for the player
+pub trait ScoreCalculator {
+    fn score(&self, result: &MoveResult, node: &State, player: Player, rng: &mut impl Rng) -> i32;
+}
+
+impl AIControl {
+    /// Searches for the best move for the player whose turn it is, awaiting
+    /// `defer` regularly. `clock` gives the current frame for
+    /// [`Budget::Frames`]. Returns `None` if there are no moves to make.
+    pub async fn find_move<F, Fut>(
+        self,
+        state: State,
+        rng: impl Rng,
+        defer: F,
+        clock: impl Fn() -> u32,
+    ) -> Option<Move>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let mut search = Search {
+            score_function: self.ai_type,
+            rng,
+            defer: &defer,
+            start_frame: clock(),
+            clock: &clock,
+            budget: self.budget,
+            table: TranspositionTable::with_memory(self.table_memory),
+            killers: Vec::new(),
+            history: vec![0; HISTORY_SLOTS * 2],
+            depth: 0,
+            nodes: 0,
+            can_stop: false,
+        };
+
+        find_best_move(state, self.depth, &mut search).await
+    }
+}
+
+/// What the search carries with it from node to node
+struct Search<'a, S, R, F, C> {
+    score_function: S,
+    rng: R,
+    defer: &'a F,
+    clock: &'a C,
+    budget: Budget,
+    table: TranspositionTable,
+    /// For each ply, the last two quiet moves there which caused a cutoff
+    killers: Vec<[Option<Move>; 2]>,
+    /// How often quiet moves caused a cutoff, for each player, weighted by how
+    /// deep the search below them was. See [`history_slot`].
+    history: Vec<u32>,
+    /// How far ahead the current iteration looks
+    depth: u32,
+    start_frame: u32,
+    /// The number of moves made so far
+    nodes: u32,
+    /// Whether the current iteration can be abandoned when the budget runs out
+    can_stop: bool,
+}
+
+impl<S, R, F, Fut, C> Search<'_, S, R, F, C>
+where
+    S: ScoreCalculator,
+    R: Rng,
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()>,
+    C: Fn() -> u32,
+{
+    fn out_of_budget(&self) -> bool {
+        match self.budget {
+            Budget::Unlimited => false,
+            Budget::Nodes(nodes) => self.nodes >= nodes,
+            Budget::Frames(frames) => (self.clock)().wrapping_sub(self.start_frame) >= frames,
+        }
+    }
+
+    fn should_stop(&self) -> bool {
+        self.can_stop && self.out_of_budget()
+    }
+
+    fn score(&mut self, result: &MoveResult, node: &State, player: Player) -> i32 {
+        self.score_function
+            .score(result, node, player, &mut self.rng)
+    }
+
+    fn execute(&mut self, node: &State, m: &Move) -> (State, MoveResult) {
+        self.nodes += 1;
+        let mut next_node = node.clone();
+        let result = next_node.execute_move(m);
+        (next_node, result)
+    }
+
+    /// Executes the move if it is legal, for moves from elsewhere in the tree
+    fn try_execute(&mut self, node: &State, m: &Move) -> Option<(State, MoveResult)> {
+        self.nodes += 1;
+        let mut next_node = node.clone();
+        let result = next_node.try_execute_move(m).ok()?;
+        Some((next_node, result))
+    }
+
+    async fn defer(&self) {
+        (self.defer)().await
+    }
+
+    /// How promising the move looks before searching it. Only moves which
+    /// have been made, with their `child`, can be seen to be tactical.
+    fn rank(&self, node: &State, m: &Move, child: Option<&(State, MoveResult)>) -> Rank {
+        match child {
+            Some((next_node, result)) if is_tactical(node, result) => Rank::Tactical {
+                score: calculate_state_score(result, next_node, node.turn()),
+            },
+            _ => Rank::Quiet {
+                history: self.history[history_slot(node, m)],
+            },
+        }
+    }
+
+    /// Remembers a move which made the search below `node` stop early, so it
+    /// can be tried sooner elsewhere
+    fn record_cutoff(&mut self, node: &State, m: &Move, result: &MoveResult, depth: u32) {
+        // tactical moves are searched early anyway
+        if is_tactical(node, result) {
+            return;
+        }
+
+        let killers = &mut self.killers[(self.depth - depth) as usize];
+        if killers[0].as_ref() != Some(m) {
+            killers[1] = killers[0].replace(m.clone());
+        }
+
+        let history = &mut self.history[history_slot(node, m)];
+        *history = history.saturating_add(depth * depth);
+    }
+}
+
+/// The order to search moves in, from last to first. Moves with the same rank
+/// are searched in a random order.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Rank {
+    Quiet {
+        history: u32,
+    },
+    /// Captures cards or changes the score, and so is likely to be the best or
+    /// worst move
+    Tactical {
+        score: i32,
+    },
+}
+
+fn is_tactical(node: &State, result: &MoveResult) -> bool {
+    let scores = node.scores();
+    !result.removed.is_empty()
+        || [Player::A, Player::B]
+            .into_iter()
+            .any(|player| result.score.player(player) != scores.player(player))
+}
+
+const HISTORY_SLOTS: usize = 512;
+
+/// Where the move's history goes. The same move is in the same slot whatever
+/// the position and platform, and different moves can share a slot.
+fn history_slot(node: &State, m: &Move) -> usize {
+    let (kind, a, b) = match m {
+        Move::PlaceCard(place) => (
+            place.direction as u32,
+            place.coordinate.0.x as u32,
+            place.coordinate.0.y as u32,
+        ),
+        Move::PushCard(push) => (4 + push.direction as u32, push.place.0, 0),
+        Move::PickCard(pick) => (8, pick.card.0, 0),
+    };
+
+    let mixed =
+        kind.wrapping_mul(0x9e37_79b9) ^ a.wrapping_mul(0x85eb_ca6b) ^ b.wrapping_mul(0xc2b2_ae35);
+
+    node.turn() as usize * HISTORY_SLOTS + (mixed >> 23) as usize
+}
+
+/// The best value of a node's moves so far, for the player choosing between
+/// them, and the window the rest are searched with
+struct Window {
+    maximising: bool,
+    alpha: i32,
+    beta: i32,
+    evaluation: i32,
+    best_move: Option<Move>,
+}
+
+impl Window {
+    fn new(maximising: bool, alpha: i32, beta: i32) -> Self {
+        Self {
+            maximising,
+            alpha,
+            beta,
+            evaluation: if maximising { i32::MIN } else { i32::MAX },
+            best_move: None,
+        }
+    }
+
+    /// Takes in the value of a move, returning whether the rest of the moves
+    /// can be skipped
+    fn update(&mut self, value: i32, m: &Move) -> bool {
+        if self.maximising {
+            if value > self.evaluation {
+                self.evaluation = value;
+                self.best_move = Some(m.clone());
+            }
+            self.alpha = self.alpha.max(self.evaluation);
+            self.beta <= self.evaluation
+        } else {
+            if value < self.evaluation {
+                self.evaluation = value;
+                self.best_move = Some(m.clone());
+            }
+            self.beta = self.beta.min(self.evaluation);
+            self.evaluation <= self.alpha
+        }
+    }
+}
+
+fn calculate_state_score(result: &MoveResult, _node: &State, current_turn: Player) -> i32 {
+    let mut score: i32 = 0;
+
+    let alternate_turn = match current_turn {
+        Player::A => Player::B,
+        Player::B => Player::A,
+    };
+
+    match result.outcome {
+        GameOutcome::Win(winner) if winner == current_turn => score += 100000000,
+        GameOutcome::Win(_) => score -= 100000000,
+        GameOutcome::Draw(_) => return 0,
+        GameOutcome::Ongoing => {}
+    }
+
+    let my_score = result.score.player(current_turn) as i32;
+    score += my_score.pow(2);
+
+    let opponent_score = result.score.player(alternate_turn) as i32;
+    score -= opponent_score.pow(3);
+
+    score
+}
+
+fn randomise_list<T>(items: &mut [T], rng: &mut impl Rng) {
+    // Randomise the move list
+    for i in (1..items.len()).rev() {
+        let j = rng.next_u32() as usize % i;
+        items.swap(i, j);
+    }
+}
+
+async fn find_best_move<S, R, F, Fut, C>(
+    game_state: State,
+    max_depth: u32,
+    search: &mut Search<'_, S, R, F, C>,
+) -> Option<Move>
+where
+    S: ScoreCalculator,
+    R: Rng,
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()>,
+    C: Fn() -> u32,
+{
+    let mut possible_moves = game_state
+        .enumerate_possible_moves_async(search.defer)
+        .await;
+
+    search.defer().await;
+
+    randomise_list(&mut possible_moves, &mut search.rng);
+
+    search.killers = vec![[None, None]; max_depth.max(1) as usize];
+
+    let mut ranked = Vec::with_capacity(possible_moves.len());
+    for m in possible_moves {
+        search.defer().await;
+        let child = search.execute(&game_state, &m);
+        let rank = search.rank(&game_state, &m, Some(&child));
+        ranked.push((m, rank));
+    }
+    // sorting is stable, so moves with the same rank stay shuffled
+    ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+    let mut possible_moves: Vec<_> = ranked.into_iter().map(|(m, _)| m).collect();
+
+    search.defer().await;
+
+    let mut best_move = None;
+
+    for depth in 1..=max_depth.max(1) {
+        search.can_stop = depth > 1;
+        search.depth = depth;
+
+        match search_to_depth(&game_state, &possible_moves, depth, search).await {
+            Some(m) => best_move = m,
+            None => break,
+        }
+
+        // the best move so far is likely to be the best in the next iteration
+        // too, and searching it first lets the others be pruned sooner
+        if let Some(index) = possible_moves
+            .iter()
+            .position(|m| Some(m) == best_move.as_ref())
+        {
+            possible_moves[..=index].rotate_right(1);
+        }
+
+        if search.out_of_budget() {
+            break;
+        }
+    }
+
+    search.defer().await;
+
+    best_move
+}
+
+/// Finds the best of the moves looking `depth` moves ahead, or `None` if the
+/// budget ran out first
+async fn search_to_depth<S, R, F, Fut, C>(
+    game_state: &State,
+    possible_moves: &[Move],
+    depth: u32,
+    search: &mut Search<'_, S, R, F, C>,
+) -> Option<Option<Move>>
+where
+    S: ScoreCalculator,
+    R: Rng,
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()>,
+    C: Fn() -> u32,
+{
+    let player = game_state.turn();
+
+    let mut alpha = i32::MIN;
+    let beta = i32::MAX;
+
+    let mut best_score = i32::MIN;
+
+    let mut best_move = None;
+
+    for move_to_check in possible_moves {
+        search.defer().await;
+        let (next_state, result) = search.execute(game_state, move_to_check);
+        let resultant_score = search.score(&result, &next_state, player);
+        search.defer().await;
+        let score = minimax(search, next_state, &result, depth - 1, player, alpha, beta).await?;
+
+        search.defer().await;
+
+        if score > best_score {
+            best_move = Some((move_to_check.clone(), score, resultant_score));
+        }
+
+        best_score = best_score.max(score);
+        alpha = best_score.max(alpha);
+    }
+
+    Some(best_move.map(|(desired_move, _, _)| desired_move))
+}
+
+/// The value of the node for `me`, or `None` if the budget ran out first
+#[async_recursion(?Send)]
+async fn minimax<S, R, F, Fut, C>(
+    search: &mut Search<'_, S, R, F, C>,
+    node: State,
+    move_result_to_get_here: &MoveResult,
+    depth: u32,
+    me: Player,
+    alpha: i32,
+    beta: i32,
+) -> Option<i32>
+where
+    S: ScoreCalculator,
+    R: Rng,
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()>,
+    C: Fn() -> u32,
+{
+    if depth == 0 || move_result_to_get_here.outcome.is_over() {
+        return Some(search.score(move_result_to_get_here, &node, me));
+    }
+
+    let key = node.hash();
+    let mut table_move = None;
+    if let Some(entry) = search.table.get(key) {
+        if u32::from(entry.depth) >= depth {
+            match entry.bound {
+                Bound::Exact => return Some(entry.score),
+                Bound::Lower if entry.score >= beta => return Some(entry.score),
+                Bound::Upper if entry.score <= alpha => return Some(entry.score),
+                _ => {}
+            }
+        }
+        table_move = entry.best_move.map(PackedMove::unpack);
+    }
+
+    if search.should_stop() {
+        return None;
+    }
+
+    let mut window = Window::new(node.turn() == me, alpha, beta);
+
+    'moves: {
+        // Finding every legal move takes as long as making them all, so the
+        // moves which were best here or nearby are tried first in case they
+        // are enough to stop
+        let mut early_moves: Vec<Move> = Vec::new();
+        let killers = search.killers[(search.depth - depth) as usize].clone();
+        for m in table_move.into_iter().chain(killers.into_iter().flatten()) {
+            if !early_moves.contains(&m) {
+                early_moves.push(m);
+            }
+        }
+
+        for m in &early_moves {
+            search.defer().await;
+            if let Some(child) = search.try_execute(&node, m) {
+                if search_move(search, &mut window, &node, m, child, depth, me).await? {
+                    break 'moves;
+                }
+            }
+        }
+
+        let mut possible_moves = node.enumerate_possible_moves_async(search.defer).await;
+        possible_moves.retain(|m| !early_moves.contains(m));
+
+        search.defer().await;
+
+        // Making the moves shows which are tactical, but isn't worth it just
+        // above the leaves where searching a move is no more work than making it
+        let mut children = Vec::new();
+        if depth >= 2 {
+            for m in &possible_moves {
+                search.defer().await;
+                children.push(Some(search.execute(&node, m)));
+            }
+        }
+
+        let mut order: Vec<(usize, Rank)> = possible_moves
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let child = children.get(i).and_then(Option::as_ref);
+                (i, search.rank(&node, m, child))
+            })
+            .collect();
+
+        // sorting is stable, so moves with the same rank stay shuffled
+        randomise_list(&mut order, &mut search.rng);
+        order.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        for (i, _) in order {
+            search.defer().await;
+            let child = match children.get_mut(i).and_then(Option::take) {
+                Some(child) => child,
+                None => search.execute(&node, &possible_moves[i]),
+            };
+            if search_move(
+                search,
+                &mut window,
+                &node,
+                &possible_moves[i],
+                child,
+                depth,
+                me,
+            )
+            .await?
+            {
+                break;
+            }
+        }
+    }
+
+    // pruning means the evaluation is only a bound on the true value when it
+    // falls outside the window the node was searched with
+    let bound = if window.evaluation <= alpha {
+        Bound::Upper
+    } else if window.evaluation >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
+
+    search.table.insert(Entry {
+        key,
+        score: window.evaluation,
+        depth: depth.min(u8::MAX.into()) as u8,
+        bound,
+        best_move: window.best_move.and_then(|m| PackedMove::pack(&m)),
+    });
+
+    Some(window.evaluation)
+}
+
+/// Searches below the node's move, returning whether the node's other moves
+/// can be skipped, or `None` if the budget ran out first
+async fn search_move<S, R, F, Fut, C>(
+    search: &mut Search<'_, S, R, F, C>,
+    window: &mut Window,
+    node: &State,
+    m: &Move,
+    (next_node, result): (State, MoveResult),
+    depth: u32,
+    me: Player,
+) -> Option<bool>
+where
+    S: ScoreCalculator,
+    R: Rng,
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()>,
+    C: Fn() -> u32,
+{
+    search.defer().await;
+    let value = minimax(
+        search,
+        next_node,
+        &result,
+        depth - 1,
+        me,
+        window.alpha,
+        window.beta,
+    )
+    .await?;
+
+    let cutoff = window.update(value, m);
+    if cutoff {
+        search.record_cutoff(node, m, &result, depth);
+    }
+    Some(cutoff)
+}
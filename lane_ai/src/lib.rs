@@ -0,0 +1,60 @@
+//! The computer player, which searches the game tree for the best move.
+//!
+//! Nothing here depends on a platform. The search is given an [`Rng`] for
+//! shuffling moves and adding noise to scores, and a `defer` hook which it
+//! awaits between pieces of work so it can be spread over many frames. The GBA
+//! passes `async_evaluator::defer`, which pauses until the next frame. Other
+//! platforms can pass [`no_defer`] and run the search with [`block_on`]. It is
+//! also given a clock counting frames, for AIs with a [`Budget::Frames`]
+//! budget, which can be [`no_clock`] if there aren't any.
+//!
+//! Searching with the generator from [`game_rng`] makes the AI's moves depend
+//! only on the game's seed, so a game between two AIs plays out the same way
+//! every time and on every platform.
+
+#![no_std]
+#![warn(clippy::all)]
+use core::{
+    future::{ready, Future, Ready},
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+extern crate alloc;
+
+mod game_tree_search;
+mod transposition;
+
+pub use game_tree_search::{AIControl, AiControlType, Budget, ScoreCalculator};
+pub use lane_logic::rng::{Pcg32, Rng};
+use lane_logic::State;
+
+/// The random numbers for the AI's search in a game with the given seed. Each
+/// turn has its own stream, so the search doesn't depend on what came before
+/// and a game can be picked up again from any position.
+pub fn game_rng(seed: u64, state: &State) -> Pcg32 {
+    Pcg32::new(seed, state.turns_played() as u64)
+}
+
+/// A `defer` hook which never pauses, for when nothing else needs to run while
+/// the search does.
+pub fn no_defer() -> Ready<()> {
+    ready(())
+}
+
+/// A clock which never moves on, so [`Budget::Frames`] never runs out.
+pub fn no_clock() -> u32 {
+    0
+}
+
+/// Runs the future until it finishes, polling it again whenever it pauses.
+pub fn block_on<O>(future: impl Future<Output = O>) -> O {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! A fixed-size table of what the search found out about the positions it has
+//! already searched, so it doesn't search them again when they are reached by
+//! another order of moves or in a deeper iteration.
+
+use alloc::vec::Vec;
+use core::{mem::size_of, num::NonZeroU64};
+
+use lane_logic::{
+    CardId, Direction, HeldCardIndex, Move, PickCardMove, PlaceCardMove, Position, PushCardMove,
+};
+
+/// What the score of an entry says about the position's true score, since
+/// alpha-beta pruning stops searching a position once it is known not to matter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Bound {
+    Exact,
+    /// The true score is at least this
+    Lower,
+    /// The true score is at most this
+    Upper,
+}
+
+/// Everything in an entry has a fixed size, so a table with the same amount of
+/// memory has the same number of entries, and the search makes the same moves,
+/// on every platform.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Entry {
+    pub(crate) key: u64,
+    pub(crate) score: i32,
+    pub(crate) depth: u8,
+    pub(crate) bound: Bound,
+    pub(crate) best_move: Option<PackedMove>,
+}
+
+/// A move packed into a number which means the same on every platform. A
+/// position reached another way can have its cards in other places, or its
+/// hand in another order, so unpacking only gives a guess at a move, which may
+/// not be legal.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PackedMove(NonZeroU64);
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+impl PackedMove {
+    /// Packs the move, unless it is too far from the start to fit
+    pub(crate) fn pack(m: &Move) -> Option<Self> {
+        let packed = match m {
+            Move::PlaceCard(place) => {
+                let x = i16::try_from(place.coordinate.0.x).ok()? as u16;
+                let y = i16::try_from(place.coordinate.0.y).ok()? as u16;
+                1 | (place.direction as u64) << 2
+                    | u64::from(u8::try_from(place.card.0).ok()?) << 8
+                    | u64::from(x) << 16
+                    | u64::from(y) << 32
+            }
+            Move::PushCard(push) => {
+                2 | (push.direction as u64) << 2 | u64::from(push.place.0) << 32
+            }
+            Move::PickCard(pick) => 3 | u64::from(pick.card.0) << 32,
+        };
+
+        NonZeroU64::new(packed).map(Self)
+    }
+
+    pub(crate) fn unpack(self) -> Move {
+        let packed = self.0.get();
+        let direction = DIRECTIONS[(packed >> 2 & 3) as usize];
+        let card = CardId((packed >> 32) as u32);
+
+        match packed & 3 {
+            1 => Move::PlaceCard(PlaceCardMove {
+                direction,
+                coordinate: Position(
+                    (
+                        i32::from((packed >> 16) as u16 as i16),
+                        i32::from((packed >> 32) as u16 as i16),
+                    )
+                        .into(),
+                ),
+                card: HeldCardIndex((packed >> 8 & 0xff) as usize),
+            }),
+            2 => Move::PushCard(PushCardMove {
+                place: card,
+                direction,
+            }),
+            _ => Move::PickCard(PickCardMove { card }),
+        }
+    }
+}
+
+pub(crate) struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    /// A table using at most `bytes` of memory. A table with no room for
+    /// entries never remembers anything.
+    pub(crate) fn with_memory(bytes: usize) -> Self {
+        let mut entries = Vec::new();
+        entries.resize(bytes / size_of::<Option<Entry>>(), None);
+        Self { entries }
+    }
+
+    fn slot(&self, key: u64) -> Option<usize> {
+        // u64 so that every platform picks the same slot
+        (!self.entries.is_empty()).then(|| (key % self.entries.len() as u64) as usize)
+    }
+
+    pub(crate) fn get(&self, key: u64) -> Option<&Entry> {
+        self.entries[self.slot(key)?]
+            .as_ref()
+            .filter(|entry| entry.key == key)
+    }
+
+    /// Stores the entry, unless its slot already holds a deeper search of the
+    /// same position.
+    pub(crate) fn insert(&mut self, entry: Entry) {
+        let Some(slot) = self.slot(entry.key) else {
+            return;
+        };
+
+        match &self.entries[slot] {
+            Some(existing) if existing.key == entry.key && existing.depth > entry.depth => {}
+            _ => self.entries[slot] = Some(entry),
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use core::{
+    cell::Cell,
+    future::{poll_fn, Future},
+    task::Poll,
+};
+
+use lane_ai::{
+    block_on, game_rng, no_clock, no_defer, AIControl, AiControlType, Budget, Pcg32,
+    ScoreCalculator,
+};
+use lane_logic::{
+    card::CardType, record::GameRecord, rules::RuleSet, GameOutcome, HeldCard, Move, MoveResult,
+    Player, State,
+};
+
+/// Pauses once each time it is awaited, like the GBA's hook does until the
+/// next frame
+fn pause(pauses: &Cell<usize>) -> impl Future<Output = ()> + '_ {
+    let mut paused = false;
+    poll_fn(move |_| {
+        if paused {
+            Poll::Ready(())
+        } else {
+            paused = true;
+            pauses.set(pauses.get() + 1);
+            Poll::Pending
+        }
+    })
+}
+
+fn hand() -> Vec<HeldCard> {
+    [
+        CardType::Block,
+        CardType::Normal,
+        CardType::Normal,
+        CardType::Ghost,
+        CardType::Double,
+    ]
+    .into_iter()
+    .map(HeldCard::Available)
+    .collect()
+}
+
+fn start() -> State {
+    State::new(hand(), hand(), Player::A, RuleSet::default())
+}
+
+/// Plays a game between two AIs as the GBA's watch mode does, returning its
+/// record
+fn watch(seed: u64) -> GameRecord {
+    let ai = AIControl {
+        depth: 1,
+        ai_type: AiControlType::WithRandom(40),
+        budget: Budget::Unlimited,
+        table_memory: 0,
+    };
+    let mut record = GameRecord::new(hand(), hand(), Player::A, RuleSet::default());
+    record.seed = Some(seed);
+    let mut state = record.initial_state();
+
+    for _ in 0..30 {
+        let Some(m) =
+            block_on(ai.find_move(state.clone(), game_rng(seed, &state), no_defer, no_clock))
+        else {
+            break;
+        };
+        record.record_move(&state, &m).unwrap();
+        if state.execute_move(&m).outcome.is_over() {
+            break;
+        }
+    }
+
+    record
+}
+
+/// The value of the position for `me` from searching every move without
+/// pruning, which the AI's search has to agree with however it orders moves
+fn full_search(node: &State, result: &MoveResult, depth: u32, me: Player) -> i32 {
+    if depth == 0 || result.outcome.is_over() {
+        return AiControlType::Best.score(result, node, me, &mut Pcg32::new(0, 0));
+    }
+
+    let values = node.enumerate_possible_moves().into_iter().map(|m| {
+        let mut next_node = node.clone();
+        let next_result = next_node.execute_move(&m);
+        full_search(&next_node, &next_result, depth - 1, me)
+    });
+
+    if node.turn() == me {
+        values.max().unwrap_or(i32::MIN)
+    } else {
+        values.min().unwrap_or(i32::MAX)
+    }
+}
+
+/// Player A wins by placing their card at (1, 3) pushing north
+fn about_to_win() -> State {
+    "
+       0   1
+  -1   N   N
+   0 (S) (S)
+   1   N   .
+   2   .   N
+turn: A
+hand A: Normal
+hand B: Normal
+    "
+    .parse()
+    .unwrap()
+}
+
+#[test]
+fn winning_moves_are_found() {
+    for (depth, table_memory) in [(1, 0), (2, 0), (3, 0), (3, 4096)] {
+        for ai_type in [AiControlType::Best, AiControlType::WithRandom(40)] {
+            let mut state = about_to_win();
+            let ai = AIControl {
+                depth,
+                ai_type,
+                budget: Budget::Unlimited,
+                table_memory,
+            };
+            let m = block_on(ai.find_move(
+                state.clone(),
+                Pcg32::new(depth as u64, 0),
+                no_defer,
+                no_clock,
+            ))
+            .unwrap();
+
+            assert_eq!(
+                state.execute_move(&m).outcome,
+                GameOutcome::Win(Player::A),
+                "{ai_type:?} at depth {depth} played {m:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn the_same_random_numbers_give_the_same_move() {
+    let ai = AIControl {
+        depth: 2,
+        ai_type: AiControlType::WithRandom(40),
+        budget: Budget::Unlimited,
+        table_memory: 0,
+    };
+
+    for seed in 0..8 {
+        let first = block_on(ai.find_move(start(), Pcg32::new(seed, 0), no_defer, no_clock));
+        let second = block_on(ai.find_move(start(), Pcg32::new(seed, 0), no_defer, no_clock));
+        assert_eq!(first, second);
+    }
+}
+
+#[test]
+fn watched_games_are_the_same_every_time() {
+    let games: Vec<_> = (0..4).map(watch).collect();
+
+    for (seed, game) in games.iter().enumerate() {
+        assert_eq!(&watch(seed as u64), game);
+    }
+    assert!(games.iter().any(|game| game.moves != games[0].moves));
+}
+
+#[test]
+fn pausing_does_not_change_the_move() {
+    let ai = AIControl {
+        depth: 2,
+        ai_type: AiControlType::Best,
+        budget: Budget::Unlimited,
+        table_memory: 0,
+    };
+    let pauses = Cell::new(0);
+
+    let without_pauses = block_on(ai.find_move(start(), Pcg32::new(3, 0), no_defer, no_clock));
+    let with_pauses =
+        block_on(ai.find_move(start(), Pcg32::new(3, 0), || pause(&pauses), no_clock));
+
+    assert_eq!(with_pauses, without_pauses);
+    assert!(pauses.get() > 0);
+}
+
+#[test]
+fn there_is_no_move_without_cards_to_play() {
+    let state = State::new(
+        vec![HeldCard::Waiting {
+            card: CardType::Normal,
+            turns_until_usable: 1,
+        }],
+        vec![HeldCard::Available(CardType::Normal)],
+        Player::A,
+        RuleSet::default(),
+    );
+    let ai = AIControl {
+        depth: 2,
+        ai_type: AiControlType::Best,
+        budget: Budget::Unlimited,
+        table_memory: 0,
+    };
+
+    assert_eq!(
+        block_on(ai.find_move(state, Pcg32::new(0, 0), no_defer, no_clock)),
+        None
+    );
+}
+
+#[test]
+fn small_budgets_still_find_winning_moves() {
+    for budget in [Budget::Nodes(1), Budget::Frames(0)] {
+        let mut state = about_to_win();
+        let ai = AIControl {
+            depth: 10,
+            ai_type: AiControlType::Best,
+            budget,
+            table_memory: 0,
+        };
+        let m = block_on(ai.find_move(state.clone(), Pcg32::new(0, 0), no_defer, no_clock));
+
+        assert_eq!(
+            state.execute_move(&m.unwrap()).outcome,
+            GameOutcome::Win(Player::A)
+        );
+    }
+}
+
+#[test]
+fn node_budgets_stop_the_search_deepening() {
+    let ai = AIControl {
+        depth: 50,
+        ai_type: AiControlType::WithRandom(40),
+        budget: Budget::Nodes(2000),
+        table_memory: 0,
+    };
+
+    let first = block_on(ai.find_move(start(), Pcg32::new(1, 0), no_defer, no_clock));
+    let second = block_on(ai.find_move(start(), Pcg32::new(1, 0), no_defer, no_clock));
+    assert!(first.is_some());
+    assert_eq!(first, second);
+}
+
+#[test]
+fn frame_budgets_go_by_the_clock() {
+    let ai = AIControl {
+        depth: 50,
+        ai_type: AiControlType::Best,
+        budget: Budget::Frames(30),
+        table_memory: 0,
+    };
+    // a frame passes every time the search pauses
+    let frame = Cell::new(0);
+    let defer = || {
+        frame.set(frame.get() + 1);
+        no_defer()
+    };
+
+    let m = block_on(ai.find_move(start(), Pcg32::new(2, 0), defer, || frame.get() / 100));
+    assert!(m.is_some());
+}
+
+#[test]
+fn tables_do_not_change_the_best_move() {
+    let ai = |table_memory| AIControl {
+        depth: 2,
+        ai_type: AiControlType::Best,
+        budget: Budget::Unlimited,
+        table_memory,
+    };
+
+    let mut state = start();
+    for turn in 0..6 {
+        let without_table =
+            block_on(ai(0).find_move(state.clone(), Pcg32::new(turn, 0), no_defer, no_clock));
+
+        // too small for any entries, room for one which different positions
+        // keep taking from each other, and plenty of room
+        for table_memory in [1, 32, 4096] {
+            let with_table = block_on(ai(table_memory).find_move(
+                state.clone(),
+                Pcg32::new(turn, 0),
+                no_defer,
+                no_clock,
+            ));
+            assert_eq!(
+                with_table, without_table,
+                "turn {turn} with {table_memory} bytes"
+            );
+        }
+
+        if state
+            .execute_move(&without_table.unwrap())
+            .outcome
+            .is_over()
+        {
+            break;
+        }
+    }
+}
+
+#[test]
+fn the_best_ai_plays_one_of_the_best_moves() {
+    let watcher = AIControl {
+        depth: 1,
+        ai_type: AiControlType::WithRandom(40),
+        budget: Budget::Unlimited,
+        table_memory: 0,
+    };
+    let mut positions = vec![start()];
+    for seed in 0..7 {
+        let mut state = positions[positions.len() - 1].clone();
+        let m = block_on(watcher.find_move(state.clone(), Pcg32::new(seed, 1), no_defer, no_clock));
+        if state.execute_move(&m.unwrap()).outcome.is_over() {
+            break;
+        }
+        positions.push(state);
+    }
+
+    // searching three moves ahead without pruning is slow, so only for a
+    // position with a single best move
+    for (depth, indices) in [(2, 0..8), (3, 4..5)] {
+        for i in indices {
+            let state = &positions[i];
+            let me = state.turn();
+            let value = |m: &Move| {
+                let mut next_state = state.clone();
+                let result = next_state.execute_move(m);
+                full_search(&next_state, &result, depth - 1, me)
+            };
+            let best = state
+                .enumerate_possible_moves()
+                .iter()
+                .map(value)
+                .max()
+                .unwrap();
+
+            for table_memory in [0, 4096] {
+                let ai = AIControl {
+                    depth,
+                    ai_type: AiControlType::Best,
+                    budget: Budget::Unlimited,
+                    table_memory,
+                };
+                let m = block_on(ai.find_move(
+                    state.clone(),
+                    Pcg32::new(i as u64, 0),
+                    no_defer,
+                    no_clock,
+                ))
+                .unwrap();
+
+                assert_eq!(
+                    value(&m),
+                    best,
+                    "position {i} at depth {depth} with {table_memory} bytes"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use lane_ai::{game_rng, AIControl};
+use lane_logic::{Move, Player, State};
+
+use crate::async_evaluator::{self, Evaluator};
+
+pub enum ControlMode {
+    TwoHuman,
+    AI(AIControl, Player),
+    TwoAI(AIControl, AIControl),
+}
+
+/// The memory for the deeper AIs' transposition tables. The heap shares the
+/// 256KiB of EWRAM with everything else, but only one search runs at a time
+/// and its table is freed when it finishes.
+pub const TABLE_MEMORY: usize = 64 * 1024;
+
+/// The AI's clock, for giving it a number of frames to think in
+static FRAMES: AtomicU32 = AtomicU32::new(0);
+
+/// Moves the AI's clock on, once per frame. The GBA only has atomic loads and
+/// stores, so this can't be a `fetch_add`.
+pub fn count_frame() {
+    FRAMES.store(
+        FRAMES.load(Ordering::SeqCst).wrapping_add(1),
+        Ordering::SeqCst,
+    );
+}
+
+/// Starts the search for the AI's move, which does a little work each time
+/// the evaluator is polled. Unless the AI has a budget in frames, the move only
+/// depends on the state and the game's seed.
+pub fn move_finder(ai: AIControl, state: State, seed: u64) -> Evaluator<Option<Move>> {
+    let rng = game_rng(seed, &state);
+    Evaluator::new(ai.find_move(state, rng, async_evaluator::defer, || {
+        FRAMES.load(Ordering::SeqCst)
+    }))
+}
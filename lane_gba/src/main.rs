@@ -19,22 +19,24 @@ use agb::{
         Font, Priority, HEIGHT, WIDTH,
     },
     fixnum::{Num, Rect, Vector2D},
+    hash_map::HashMap,
     include_aseprite,
     input::{Button, ButtonController},
     interrupt::VBlank,
     sound::mixer::{Frequency, Mixer, SoundChannel},
 };
+use ai::ControlMode;
 use alloc::vec::Vec;
 use async_evaluator::Evaluator;
-use game_tree_search::{AIControl, AiControlType, ControlMode};
+use lane_ai::{AIControl, AiControlType, Budget};
 use lane_logic::{
-    card::CardType, Direction, HeldCard, HeldCardIndex, Index, Move, MoveResult, PickCardMove,
-    PlaceCardMove, Player, Position, PushCardMove, State,
+    card::CardType, notation::MoveNotation, record::GameRecord, rules::RuleSet, BoardEvent, CardId,
+    Direction, GameOutcome, HeldCard, HeldCardIndex, Move, MoveResult, PickCardMove, PlaceCardMove,
+    Player, Position, PushCardMove, State,
 };
-use slotmap::{DefaultKey, SecondaryMap};
 
+mod ai;
 mod async_evaluator;
-mod game_tree_search;
 
 const FONT_20: Font = agb::include_font!("fnt/VCR_OSD_MONO_1.001.ttf", 20);
 
@@ -80,6 +82,8 @@ fn card_type_to_sprite(t: CardType) -> &'static Sprite {
         CardType::Score => deconstify!(CARDS.tags().get("Score")).sprite(0),
         CardType::Redirect => deconstify!(CARDS.tags().get("Redirect")).sprite(0),
         CardType::Reverse => deconstify!(CARDS.tags().get("Reverse")).sprite(0),
+        // the game doesn't have any custom cards, so there's no art for them
+        CardType::Custom(_) => deconstify!(CARDS.tags().get("Normal")).sprite(0),
     }
 }
 
@@ -98,6 +102,7 @@ fn card_type_to_sprite_double(t: CardType) -> &'static Sprite {
         CardType::Score => deconstify!(CARDS.tags().get("Score Double")).sprite(0),
         CardType::Redirect => deconstify!(CARDS.tags().get("Redirect Double")).sprite(0),
         CardType::Reverse => deconstify!(CARDS.tags().get("Reverse Double")).sprite(0),
+        CardType::Custom(_) => deconstify!(CARDS.tags().get("Normal Double")).sprite(0),
     }
 }
 
@@ -141,9 +146,11 @@ struct CardInHand<'controller> {
 }
 
 struct MyState<'controller> {
-    cards: SecondaryMap<DefaultKey, CardOnBoard<'controller>>,
-    playing_animations: Vec<Vec<(Index, CardAnimationStatus)>>,
+    cards: HashMap<CardId, CardOnBoard<'controller>>,
+    playing_animations: Vec<Vec<(CardId, CardAnimationStatus)>>,
     game_state: State,
+    /// The game so far, including the seed the AI searches with
+    record: GameRecord,
     select: SelectBox<'controller>,
     camera_position: Vector2D<Num<i32, 8>>,
     select_arrow: Option<Object<'controller>>,
@@ -151,7 +158,7 @@ struct MyState<'controller> {
     move_finder: Option<Evaluator<Option<Move>>>,
     control_mode: ControlMode,
     pick_help: PickHelp<'controller>,
-    winner: Option<Player>,
+    outcome: GameOutcome,
 }
 
 struct PickHelp<'controller> {
@@ -257,11 +264,12 @@ impl<'controller> MyState<'controller> {
         }
     }
 
-    fn new(initial_state: State, object: &'controller OamManaged, control: ControlMode) -> Self {
+    fn new(record: GameRecord, object: &'controller OamManaged, control: ControlMode) -> Self {
         let mut state = MyState {
             cards: Default::default(),
             playing_animations: Default::default(),
-            game_state: initial_state,
+            game_state: record.initial_state(),
+            record,
             select: SelectBox {
                 object: object.object_sprite(SELECT),
                 pick_box: object.object_sprite(SELECT_DOUBLE),
@@ -273,14 +281,14 @@ impl<'controller> MyState<'controller> {
             move_finder: None,
             control_mode: control,
             pick_help: PickHelp::new(object),
-            winner: None,
+            outcome: GameOutcome::Ongoing,
         };
 
         state.pick_help.hide();
 
-        for (idx, card) in state.game_state.board_state() {
+        for (_, card) in state.game_state.board_state() {
             state.cards.insert(
-                idx.to_slotmap_key(),
+                card.id,
                 CardOnBoard {
                     card_object: object.object_sprite(card_type_to_sprite(card.card.to_type())),
                     colour_object: card
@@ -341,7 +349,7 @@ impl<'controller> MyState<'controller> {
             }
         }
 
-        if self.winner.is_none() && self.move_finder.is_none() {
+        if !self.outcome.is_over() && self.move_finder.is_none() {
             match self.control_mode {
                 ControlMode::TwoHuman => {}
                 ControlMode::AI(ai, player) => {
@@ -358,22 +366,27 @@ impl<'controller> MyState<'controller> {
             }
         }
 
-        if self.playing_animations.is_empty() && self.winner.is_none() {
+        if self.playing_animations.is_empty() && !self.outcome.is_over() {
             match self.control_mode {
                 ControlMode::TwoHuman => {
                     self.do_human_turn(position_difference, input, object, mixer);
-                    match self.winner {
-                        Some(Player::A) => text.write(
+                    match self.outcome {
+                        GameOutcome::Win(Player::A) => text.write(
                             &FONT_20,
                             (10_u16, 10_u16).into(),
                             format_args!("The winner is\n  Player A"),
                         ),
-                        Some(Player::B) => text.write(
+                        GameOutcome::Win(Player::B) => text.write(
                             &FONT_20,
                             (10_u16, 10_u16).into(),
                             format_args!("The winner is\n  Player B"),
                         ),
-                        None => {}
+                        GameOutcome::Draw(_) => text.write(
+                            &FONT_20,
+                            (10_u16, 10_u16).into(),
+                            format_args!("It's a draw"),
+                        ),
+                        GameOutcome::Ongoing => {}
                     }
                 }
                 ControlMode::AI(ai, player) => {
@@ -383,7 +396,9 @@ impl<'controller> MyState<'controller> {
                         self.do_human_turn(position_difference, input, object, mixer);
                     }
 
-                    if let Some(p) = self.winner {
+                    if let GameOutcome::Draw(_) = self.outcome {
+                        text.write(&FONT_20, (5_u16, 5_u16).into(), format_args!("It's a draw"))
+                    } else if let GameOutcome::Win(p) = self.outcome {
                         if p == player {
                             text.write(
                                 &FONT_20,
@@ -404,23 +419,26 @@ impl<'controller> MyState<'controller> {
                         Player::A => self.do_ai_turn(ai1, object),
                         Player::B => self.do_ai_turn(ai2, object),
                     };
-                    match self.winner {
-                        Some(Player::A) => text.write(
+                    match self.outcome {
+                        GameOutcome::Win(Player::A) => text.write(
                             &FONT_20,
                             (5_u16, 5_u16).into(),
                             format_args!("The winner is\n    AI 1"),
                         ),
-                        Some(Player::B) => text.write(
+                        GameOutcome::Win(Player::B) => text.write(
                             &FONT_20,
                             (5_u16, 5_u16).into(),
                             format_args!("The winner is\n    AI 2"),
                         ),
-                        None => {}
+                        GameOutcome::Draw(_) => {
+                            text.write(&FONT_20, (5_u16, 5_u16).into(), format_args!("It's a draw"))
+                        }
+                        GameOutcome::Ongoing => {}
                     }
                 }
             };
 
-            if self.winner.is_some() {
+            if self.outcome.is_over() {
                 text.write(
                     &FONT_15,
                     (8_u16, 18_u16).into(),
@@ -431,8 +449,13 @@ impl<'controller> MyState<'controller> {
     }
 
     fn prepare_ai_turn(&mut self, ai_mode: AIControl) {
-        self.move_finder
-            .get_or_insert_with(|| ai_mode.move_finder(self.game_state.clone()));
+        self.move_finder.get_or_insert_with(|| {
+            ai::move_finder(
+                ai_mode,
+                self.game_state.clone(),
+                self.record.seed.unwrap_or_default(),
+            )
+        });
     }
 
     fn do_ai_turn(
@@ -444,15 +467,23 @@ impl<'controller> MyState<'controller> {
         self.select.pick_box.hide();
         self.pick_help.hide();
 
-        let move_finder = self
-            .move_finder
-            .get_or_insert_with(|| ai_mode.move_finder(self.game_state.clone()));
+        let move_finder = self.move_finder.get_or_insert_with(|| {
+            ai::move_finder(
+                ai_mode,
+                self.game_state.clone(),
+                self.record.seed.unwrap_or_default(),
+            )
+        });
 
         if let Some(m) = move_finder.result() {
-            let m = m.as_ref().unwrap();
+            // the game ends in a draw before the AI is asked to move with no moves left
+            let m = m
+                .as_ref()
+                .expect("the AI should always have a move to make");
+            self.record.record_move(&self.game_state, m);
             let result = self.game_state.execute_move(m);
 
-            self.winner = result.winner;
+            self.outcome = result.outcome;
 
             self.update_representation(&result, object);
 
@@ -479,12 +510,14 @@ impl<'controller> MyState<'controller> {
         if let Some(desired_move) =
             self.update_select_box(position_difference, input, object, mixer)
         {
+            // the record needs the move in terms of the board before it's made
+            let notation = MoveNotation::from_move(&self.game_state, &desired_move);
+
             // validate the move is possible
-            if self.game_state.can_execute_move(&desired_move) {
+            if let Ok(result) = self.game_state.try_execute_move(&desired_move) {
                 // woah!
-                let result = self.game_state.execute_move(&desired_move);
-
-                self.winner = result.winner;
+                self.record.moves.extend(notation);
+                self.outcome = result.outcome;
 
                 self.update_representation(&result, object);
 
@@ -582,7 +615,7 @@ impl<'controller> MyState<'controller> {
                 if input.is_just_pressed(Button::A) {
                     if reason == BoardSelect::Pick {
                         if let Some(card) = self.game_state.card_at_position(Position(position)) {
-                            return Some(Move::PickCard(PickCardMove { card: card.0 }));
+                            return Some(Move::PickCard(PickCardMove { card: card.1.id }));
                         } else {
                             mixer.play_sound(SoundChannel::new(INCORRECT));
                         }
@@ -662,10 +695,10 @@ impl<'controller> MyState<'controller> {
                         // execute a move!
                         let desired_move = (|| match reason {
                             BoardSelect::Push => {
-                                let (card, _place) =
+                                let (_, card) =
                                     self.game_state.card_at_position(Position(position))?;
                                 Some(Move::PushCard(PushCardMove {
-                                    place: card,
+                                    place: card.id,
                                     direction,
                                 }))
                             }
@@ -692,9 +725,9 @@ impl<'controller> MyState<'controller> {
 
     fn update_representation(&mut self, update: &MoveResult, object: &'controller OamManaged) {
         // add the newly placed cards
-        for (idx, direction, new_card) in &update.placed {
+        for (_, direction, new_card) in &update.placed {
             self.cards.insert(
-                idx.to_slotmap_key(),
+                new_card.id,
                 CardOnBoard {
                     card_object: {
                         let mut obj =
@@ -721,47 +754,41 @@ impl<'controller> MyState<'controller> {
                 },
             );
         }
-        self.playing_animations.push(
-            update
-                .placed
-                .iter()
-                .map(|(idx, _, new_card)| {
-                    (
-                        *idx,
-                        CardAnimationStatus::Placed(
-                            new_card
-                                .position
-                                .0
-                                .change_base()
-                                .hadamard(CONVERSION_FACTOR),
-                        ),
-                    )
-                })
-                .collect(),
-        );
+        // play the move back one step at a time, in the order things happened
+        let mut steps: Vec<Vec<(CardId, CardAnimationStatus)>> = Vec::new();
+        for event in &update.events {
+            let (card, animation) = match event {
+                BoardEvent::Placed { card, position, .. } => (
+                    *card,
+                    CardAnimationStatus::Placed(
+                        position.0.change_base().hadamard(CONVERSION_FACTOR),
+                    ),
+                ),
+                BoardEvent::Moved { card, to, .. } => (
+                    *card,
+                    CardAnimationStatus::MoveTowards(
+                        to.0.change_base().hadamard(CONVERSION_FACTOR),
+                    ),
+                ),
+                BoardEvent::Captured { card, .. } | BoardEvent::ReturnedToHand { card, .. } => {
+                    (*card, CardAnimationStatus::Dying)
+                }
+                BoardEvent::ScoreChanged { .. } => continue,
+            };
 
-        self.playing_animations.push(
-            update
-                .moved
-                .iter()
-                .map(|(idx, card)| {
-                    (
-                        *idx,
-                        CardAnimationStatus::MoveTowards(
-                            card.position.0.change_base().hadamard(CONVERSION_FACTOR),
-                        ),
-                    )
-                })
-                .collect(),
-        );
+            if steps.len() <= event.step() {
+                steps.resize_with(event.step() + 1, Vec::new);
+            }
 
-        self.playing_animations.push(
-            update
-                .removed
-                .iter()
-                .map(|(idx, _)| (*idx, CardAnimationStatus::Dying))
-                .collect(),
-        );
+            let step = &mut steps[event.step()];
+            match step.iter_mut().find(|(other, _)| *other == card) {
+                Some(existing) => existing.1 = animation,
+                None => step.push((card, animation)),
+            }
+        }
+
+        self.playing_animations
+            .extend(steps.into_iter().filter(|step| !step.is_empty()));
     }
 
     fn update_animation(&mut self) -> CompletedAnimation {
@@ -774,15 +801,15 @@ impl<'controller> MyState<'controller> {
         for (card, animation) in animations_to_run {
             match animation {
                 CardAnimationStatus::Placed(destination) => {
-                    let current = self.cards[card.to_slotmap_key()].position;
-                    self.cards[card.to_slotmap_key()].position += (*destination - current)
-                        .fast_normalise()
+                    let card = self.cards.get_mut(card).unwrap();
+                    let current = card.position;
+                    card.position += (*destination - current).fast_normalise()
                         * ((*destination - current).manhattan_distance().min(8.into()))
                 }
                 CardAnimationStatus::MoveTowards(destination) => {
-                    let current = self.cards[card.to_slotmap_key()].position;
-                    self.cards[card.to_slotmap_key()].position += (*destination - current)
-                        .fast_normalise()
+                    let card = self.cards.get_mut(card).unwrap();
+                    let current = card.position;
+                    card.position += (*destination - current).fast_normalise()
                         * ((*destination - current).manhattan_distance().min(4.into()))
                 }
                 CardAnimationStatus::Dying => { /* TODO: Death affect */ }
@@ -795,7 +822,7 @@ impl<'controller> MyState<'controller> {
         for (idx, animation) in playing_animations
             .extract_if(|(idx, animation)| match animation {
                 CardAnimationStatus::Placed(pos) | CardAnimationStatus::MoveTowards(pos) => {
-                    (cards[idx.to_slotmap_key()].position - *pos).manhattan_distance() < 1.into()
+                    (cards[&*idx].position - *pos).manhattan_distance() < 1.into()
                 }
                 CardAnimationStatus::Dying => true, /* TODO: Death finaliser */
             })
@@ -803,11 +830,12 @@ impl<'controller> MyState<'controller> {
         {
             match animation {
                 CardAnimationStatus::Placed(pos) | CardAnimationStatus::MoveTowards(pos) => {
-                    self.cards[idx.to_slotmap_key()].position = pos;
-                    self.cards[idx.to_slotmap_key()].counts_to_average = true;
+                    let card = self.cards.get_mut(&idx).unwrap();
+                    card.position = pos;
+                    card.counts_to_average = true;
                 }
                 CardAnimationStatus::Dying => {
-                    self.cards.remove(idx.to_slotmap_key());
+                    self.cards.remove(&idx);
                 }
             }
         }
@@ -1015,6 +1043,8 @@ impl<'controller> Menu<'controller> {
                     AIControl {
                         depth: 1,
                         ai_type: AiControlType::Negative,
+                        budget: Budget::Unlimited,
+                        table_memory: 0,
                     },
                     Player::B,
                 )),
@@ -1022,6 +1052,8 @@ impl<'controller> Menu<'controller> {
                     AIControl {
                         depth: 1,
                         ai_type: AiControlType::WithRandom(40),
+                        budget: Budget::Unlimited,
+                        table_memory: 0,
                     },
                     Player::B,
                 )),
@@ -1029,13 +1061,18 @@ impl<'controller> Menu<'controller> {
                     AIControl {
                         depth: 1,
                         ai_type: AiControlType::Best,
+                        budget: Budget::Unlimited,
+                        table_memory: 0,
                     },
                     Player::B,
                 )),
+                // thinks for as long as it can in a second and a half
                 3 => Some(ControlMode::AI(
                     AIControl {
-                        depth: 2,
+                        depth: 4,
                         ai_type: AiControlType::Best,
+                        budget: Budget::Frames(90),
+                        table_memory: ai::TABLE_MEMORY,
                     },
                     Player::B,
                 )),
@@ -1043,20 +1080,30 @@ impl<'controller> Menu<'controller> {
                     AIControl {
                         depth: 1,
                         ai_type: AiControlType::WithRandom(40),
+                        budget: Budget::Unlimited,
+                        table_memory: 0,
                     },
                     AIControl {
                         depth: 1,
                         ai_type: AiControlType::WithRandom(40),
+                        budget: Budget::Unlimited,
+                        table_memory: 0,
                     },
                 )),
+                // a budget in nodes rather than frames so that the game only
+                // depends on its seed
                 5 => Some(ControlMode::TwoAI(
                     AIControl {
-                        depth: 2,
+                        depth: 4,
                         ai_type: AiControlType::WithRandom(40),
+                        budget: Budget::Nodes(2000),
+                        table_memory: ai::TABLE_MEMORY,
                     },
                     AIControl {
-                        depth: 2,
+                        depth: 4,
                         ai_type: AiControlType::WithRandom(40),
+                        budget: Budget::Nodes(2000),
+                        table_memory: ai::TABLE_MEMORY,
                     },
                 )),
                 6 => Some(ControlMode::TwoHuman),
@@ -1095,7 +1142,7 @@ fn battle(gba: &mut agb::Gba) {
     mist_city.should_loop();
     mixer.play_sound(mist_city);
 
-    let game_state = State::new(
+    let record = GameRecord::new(
         alloc::vec![
             HeldCard::Available(CardType::Block),
             HeldCard::Available(CardType::Normal),
@@ -1111,6 +1158,7 @@ fn battle(gba: &mut agb::Gba) {
             HeldCard::Available(CardType::Double)
         ],
         Player::A,
+        RuleSet::default(),
     );
 
     loop {
@@ -1133,7 +1181,11 @@ fn battle(gba: &mut agb::Gba) {
 
         text_render.clear();
         {
-            let mut state = MyState::new(game_state.clone(), &object, mode);
+            let mut record = record.clone();
+            record.seed =
+                Some((agb::rng::gen() as u32 as u64) << 32 | agb::rng::gen() as u32 as u64);
+
+            let mut state = MyState::new(record, &object, mode);
 
             loop {
                 mixer.frame();
@@ -1152,13 +1204,14 @@ fn battle(gba: &mut agb::Gba) {
                 }
 
                 vblank.wait_for_vblank();
+                ai::count_frame();
                 text_render.commit();
                 object.commit();
                 input.update();
 
                 state.frame(&object, &input, &mut mixer, &mut text_render);
 
-                if input.is_just_pressed(Button::START) && state.winner.is_some() {
+                if input.is_just_pressed(Button::START) && state.outcome.is_over() {
                     break;
                 }
             }
@@ -0,0 +1,158 @@
+use alloc::vec::Vec;
+use slotmap::{DefaultKey, Key, KeyData};
+
+/// A generational arena that hands out slotmap keys, like `HopSlotMap`, but
+/// which can also put things back exactly as they were. Undoing a move needs
+/// removed cards to come back under the key they had before, and undoing a
+/// placement needs the next insert to hand out the same key again.
+///
+/// Versions follow the slotmap convention of odd for occupied slots and even
+/// for vacant ones.
+#[derive(Debug, Clone)]
+pub(crate) struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    version: u32,
+    value: Option<T>,
+}
+
+fn make_key(idx: u32, version: u32) -> DefaultKey {
+    KeyData::from_ffi((u64::from(version) << 32) | u64::from(idx)).into()
+}
+
+fn split_key(key: DefaultKey) -> (u32, u32) {
+    let ffi = key.data().as_ffi();
+    (ffi as u32, (ffi >> 32) as u32)
+}
+
+impl<T> Arena<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn insert(&mut self, value: T) -> DefaultKey {
+        let idx = match self.free.pop() {
+            Some(idx) => idx,
+            None => {
+                self.slots.push(Slot {
+                    version: 0,
+                    value: None,
+                });
+                self.slots.len() as u32 - 1
+            }
+        };
+
+        let slot = &mut self.slots[idx as usize];
+        slot.version += 1;
+        slot.value = Some(value);
+        self.len += 1;
+
+        make_key(idx, slot.version)
+    }
+
+    pub(crate) fn remove(&mut self, key: DefaultKey) -> Option<T> {
+        let (idx, version) = split_key(key);
+        let slot = self.slots.get_mut(idx as usize)?;
+        if slot.version != version {
+            return None;
+        }
+
+        let value = slot.value.take()?;
+        slot.version += 1;
+        self.free.push(idx);
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    /// Reverses the most recent [`Arena::insert`] which returned `key`.
+    pub(crate) fn uninsert(&mut self, key: DefaultKey) -> Option<T> {
+        let (idx, version) = split_key(key);
+        let slot = self.slots.get_mut(idx as usize)?;
+        if slot.version != version {
+            return None;
+        }
+
+        let value = slot.value.take()?;
+        slot.version -= 1;
+        self.len -= 1;
+
+        if slot.version == 0 && idx as usize == self.slots.len() - 1 {
+            self.slots.pop();
+        } else {
+            self.free.push(idx);
+        }
+
+        Some(value)
+    }
+
+    /// Reverses the most recent [`Arena::remove`] of `key`, putting the value
+    /// back under the same key.
+    pub(crate) fn unremove(&mut self, key: DefaultKey, value: T) {
+        let (idx, version) = split_key(key);
+
+        match self.free.last() {
+            Some(&last) if last == idx => {
+                self.free.pop();
+            }
+            _ => self.free.retain(|&free| free != idx),
+        }
+
+        let slot = &mut self.slots[idx as usize];
+        debug_assert!(slot.value.is_none() && slot.version == version + 1);
+        slot.version = version;
+        slot.value = Some(value);
+        self.len += 1;
+    }
+
+    pub(crate) fn get(&self, key: DefaultKey) -> Option<&T> {
+        let (idx, version) = split_key(key);
+        self.slots
+            .get(idx as usize)
+            .filter(|slot| slot.version == version)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub(crate) fn get_mut(&mut self, key: DefaultKey) -> Option<&mut T> {
+        let (idx, version) = split_key(key);
+        self.slots
+            .get_mut(idx as usize)
+            .filter(|slot| slot.version == version)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (DefaultKey, &T)> {
+        self.slots.iter().enumerate().filter_map(|(idx, slot)| {
+            slot.value
+                .as_ref()
+                .map(|value| (make_key(idx as u32, slot.version), value))
+        })
+    }
+}
+
+impl<T> core::ops::Index<DefaultKey> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, key: DefaultKey) -> &Self::Output {
+        self.get(key).expect("invalid arena key")
+    }
+}
+
+impl<T> core::ops::IndexMut<DefaultKey> for Arena<T> {
+    fn index_mut(&mut self, key: DefaultKey) -> &mut Self::Output {
+        self.get_mut(key).expect("invalid arena key")
+    }
+}
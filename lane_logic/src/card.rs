@@ -1,10 +1,32 @@
-use crate::{Board, Direction, Index, PlaceStatus, Player, Position, PushStatus, Set};
+use crate::{Board, Direction, Index, PlaceStatus, Player, Position, PushRefusal, Set};
 
-trait Card: Default + core::fmt::Debug + Clone {
-    fn push(board: &mut Board, self_index: Index, direction: Direction, depth: usize)
-        -> Set<Index>;
-    fn can_push(board: &Board, self_index: Index, direction: Direction, depth: usize)
-        -> PushStatus;
+/// The behaviour of a card type.
+///
+/// Implement this to add a card outside of this crate, and then make a
+/// [`CustomCardType`] for it to put it in a hand. The `chain` passed to
+/// `push` should be passed on to [`CardData::push`] when pushing other cards.
+///
+/// Pushes are resolved depth first. A card pushes whatever is in its way
+/// before moving itself, and only moves if the space it is moving into is
+/// empty by then, so two cards are never in the same place. A card which is
+/// pushed straight back the way it came while it is part way through being
+/// pushed, as by a [`reverse::Reverse`], bounces back instead and so must not
+/// move again once the cards in its way are pushed. It only bounces once. Any
+/// other push which reaches a card that is part way through being pushed has
+/// gone round in a circle. That card refuses to move, which blocks the chain
+/// leading back to it just like a [`block::Block`] would, and the move is
+/// reported as a [`crate::MoveError::PushCycle`].
+///
+/// Whether a push is legal is found by making it, so a card only has to say
+/// how it moves. A push which moves nothing is blocked, and one which only
+/// slides the pushed card along empty board has nothing to push.
+pub trait Card: Default + core::fmt::Debug + Clone {
+    fn push(
+        board: &mut Board,
+        self_index: Index,
+        direction: Direction,
+        chain: &PushChain,
+    ) -> Set<Index>;
     fn can_place(
         board: &Board,
         player: Player,
@@ -19,45 +41,63 @@ trait Card: Default + core::fmt::Debug + Clone {
     ) -> (Index, Set<Index>);
 }
 
+type Pusher = fn(&mut Board, Index, Direction, &PushChain) -> Set<Index>;
+type Placer = fn(&mut Board, Player, Position, Direction) -> (Index, Set<Index>);
+type CanPlacer = fn(&Board, Player, Position, Direction) -> PlaceStatus;
+
 macro_rules! create_card_data{
     ($name:ident, $type_name:ident, $( $card_type: ident ),+) => {
 
         #[derive(Debug, Clone)]
         pub enum $name {
-            $($card_type ($card_type)),+
+            $($card_type ($card_type),)+
+            Custom(CustomCardType),
         }
 
         #[derive(Debug, Clone, Copy, PartialEq, Eq)]
         pub enum $type_name {
-            $($card_type),+
+            $($card_type,)+
+            Custom(CustomCardType),
         }
 
         impl $type_name {
-            pub(crate) fn to_data(self) -> $name {
+            pub fn name(self) -> &'static str {
                 match self {
-                    $( $type_name::$card_type => $name::$card_type(Default::default())),+
+                    $( $type_name::$card_type => stringify!($card_type),)+
+                    $type_name::Custom(custom) => custom.name,
                 }
             }
-        }
 
-        impl $name {
-            pub fn to_type(&self) -> $type_name {
+            /// Finds a built in card by its name. Custom cards aren't known here.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $( stringify!($card_type) => Some($type_name::$card_type),)+
+                    _ => None,
+                }
+            }
+
+            pub(crate) fn to_data(self) -> $name {
                 match self {
-                    $( $name::$card_type(_) => $type_name::$card_type),+
+                    $( $type_name::$card_type => $name::$card_type(Default::default()),)+
+                    $type_name::Custom(custom) => $name::Custom(custom),
                 }
             }
         }
 
         impl $name {
-            fn pusher(&self) -> fn(&mut Board, Index, Direction, usize) -> Set<Index> {
+            pub fn to_type(&self) -> $type_name {
                 match self {
-                    $( $name::$card_type(_) => $card_type::push),+
+                    $( $name::$card_type(_) => $type_name::$card_type,)+
+                    $name::Custom(custom) => $type_name::Custom(*custom),
                 }
             }
+        }
 
-            fn can_pusher(&self) -> fn(&Board, Index, Direction, usize) -> PushStatus {
+        impl $name {
+            fn pusher(&self) -> Pusher {
                 match self {
-                    $( $name::$card_type(_) => $card_type::can_push),+
+                    $( $name::$card_type(_) => $card_type::push,)+
+                    $name::Custom(custom) => custom.push,
                 }
             }
 
@@ -69,7 +109,8 @@ macro_rules! create_card_data{
                 direction: Direction,
             ) -> (Index, Set<Index>) {
                 match card {
-                    $( $type_name::$card_type => $card_type::place(board, player, position, direction)),+
+                    $( $type_name::$card_type => $card_type::place(board, player, position, direction),)+
+                    $type_name::Custom(custom) => (custom.place)(board, player, position, direction),
                 }
             }
 
@@ -81,7 +122,8 @@ macro_rules! create_card_data{
                 direction: Direction,
             ) -> PlaceStatus {
                 match card {
-                    $( $type_name::$card_type => $card_type::can_place(board, player, position, direction)),+
+                    $( $type_name::$card_type => $card_type::can_place(board, player, position, direction),)+
+                    $type_name::Custom(custom) => (custom.can_place)(board, player, position, direction),
                 }
             }
 
@@ -93,7 +135,7 @@ macro_rules! create_card_data{
         impl $card_type {
 
             fn get_self_mut(board: &mut Board, self_idx: Index) -> &mut $card_type {
-                match &mut board[self_idx].card {
+                match &mut board.card_mut(self_idx).card {
                     $name::$card_type(a) => a,
                     _ => panic!("you've got the wrong card!")
                 }
@@ -115,23 +157,118 @@ macro_rules! create_card_data{
     }
 }
 
+/// A card type defined outside of this crate.
+///
+/// Two custom card types are the same if they have the same name, so the name
+/// should be unique and not clash with any of the built in cards. A card which
+/// places itself with [`normal::normal_placement`] should pass it
+/// `CardData::Custom` of its own custom card type.
+#[derive(Clone, Copy)]
+pub struct CustomCardType {
+    name: &'static str,
+    push: Pusher,
+    place: Placer,
+    can_place: CanPlacer,
+}
+
+impl CustomCardType {
+    pub const fn new<C: Card>(name: &'static str) -> Self {
+        Self {
+            name,
+            push: C::push,
+            place: C::place,
+            can_place: C::can_place,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl PartialEq for CustomCardType {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CustomCardType {}
+
+impl core::fmt::Debug for CustomCardType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CustomCardType").field(&self.name).finish()
+    }
+}
+
+/// The cards which are part way through being pushed, from the most recently
+/// pushed back to the card the push started with.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PushChain<'a> {
+    link: Option<(Index, Direction, &'a PushChain<'a>)>,
+}
+
+impl<'a> PushChain<'a> {
+    /// Whether the card is already being pushed.
+    pub fn contains(&self, index: Index) -> bool {
+        self.iter().any(|card| card == index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.link.is_none()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Index> + '_ {
+        self.links().map(|(card, _)| card)
+    }
+
+    /// Whether pushing the card this way sends it straight back the way it is
+    /// already being pushed, for the first time.
+    fn bounces(&self, index: Index, direction: Direction) -> bool {
+        let mut pushes = self.links().filter(|&(card, _)| card == index);
+        matches!(
+            (pushes.next(), pushes.next()),
+            (Some((_, pushed)), None) if pushed == -direction
+        )
+    }
+
+    fn links(&self) -> impl Iterator<Item = (Index, Direction)> + '_ {
+        core::iter::successors(self.link, |(_, _, rest)| rest.link)
+            .map(|(card, direction, _)| (card, direction))
+    }
+
+    fn then(&'a self, index: Index, direction: Direction) -> PushChain<'a> {
+        PushChain {
+            link: Some((index, direction, self)),
+        }
+    }
+}
+
 impl CardData {
-    pub(crate) fn push(
+    /// Pushes the card at `index`, returning every card that moved. The push
+    /// did nothing if this is empty, which is always the case if the card is
+    /// already in the chain and isn't being bounced back.
+    pub fn push(
         board: &mut Board,
         index: Index,
         direction: Direction,
-        depth: usize,
+        chain: &PushChain,
     ) -> Set<Index> {
-        board[index].card.pusher()(board, index, direction, depth + 1)
-    }
+        if chain.contains(index) && !chain.bounces(index, direction) {
+            board.refuse(PushRefusal::Cycle(index));
+            return Set::new();
+        }
 
-    pub(crate) fn can_push(
-        board: &Board,
-        index: Index,
-        direction: Direction,
-        depth: usize,
-    ) -> PushStatus {
-        board[index].card.can_pusher()(board, index, direction, depth + 1)
+        let moved =
+            board[index].card.pusher()(board, index, direction, &chain.then(index, direction));
+        if moved.is_empty() {
+            board.refuse(PushRefusal::Blocked(index));
+        }
+
+        moved
     }
 }
 
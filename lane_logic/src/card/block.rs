@@ -1,6 +1,6 @@
-use crate::{Board, Direction, Index, PlaceStatus, Player, Position, PushStatus, Set};
+use crate::{Board, Direction, Index, PlaceStatus, Player, Position, Set};
 
-use super::{normal::Normal, Card};
+use super::{normal::Normal, Card, PushChain};
 
 #[derive(Debug, Clone, Default)]
 pub struct Block {}
@@ -10,20 +10,11 @@ impl Card for Block {
         _board: &mut Board,
         _self_indexx: Index,
         _direction: Direction,
-        _depth: usize,
+        _chain: &PushChain,
     ) -> Set<Index> {
         Set::new()
     }
 
-    fn can_push(
-        _board: &Board,
-        _self_index: Index,
-        _direction: Direction,
-        _depth: usize,
-    ) -> PushStatus {
-        PushStatus::Fail
-    }
-
     fn can_place(
         board: &Board,
         player: Player,
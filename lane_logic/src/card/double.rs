@@ -1,6 +1,6 @@
-use crate::{Board, Direction, Index, PlaceStatus, Player, Position, PushStatus, Set};
+use crate::{Board, Direction, Index, PlaceStatus, Player, Position, Set};
 
-use super::{normal::Normal, Card, CardData};
+use super::{normal::Normal, Card, PushChain};
 
 #[derive(Debug, Clone, Default)]
 pub struct Double {}
@@ -10,46 +10,20 @@ impl Card for Double {
         board: &mut Board,
         self_index: Index,
         direction: Direction,
-        depth: usize,
+        chain: &PushChain,
     ) -> Set<Index> {
-        let pushed = Normal::push(board, self_index, direction, depth);
+        let pushed = Normal::push(board, self_index, direction, chain);
         if pushed.is_empty() {
             return pushed;
         }
 
-        Normal::push(board, self_index, direction, depth)
+        board.next_step();
+        Normal::push(board, self_index, direction, chain)
             .union(&pushed)
             .cloned()
             .collect()
     }
 
-    fn can_push(
-        board: &Board,
-        self_index: Index,
-        direction: Direction,
-        depth: usize,
-    ) -> PushStatus {
-        match Normal::can_push(board, self_index, direction, depth) {
-            PushStatus::Success(n @ 1..) => PushStatus::Success(n),
-            PushStatus::Success(0) | PushStatus::Fail => {
-                // check if our double push can push
-
-                let my_position = board[self_index].position;
-                let next_position = my_position + direction + direction;
-
-                // find index of next item
-                if let Some(next_index) = board.get_card_position(next_position) {
-                    match CardData::can_push(board, next_index, direction, depth) {
-                        PushStatus::Success(n) => PushStatus::Success(n + 1),
-                        PushStatus::Fail => PushStatus::Success(0),
-                    }
-                } else {
-                    PushStatus::Success(0)
-                }
-            }
-        }
-    }
-
     fn can_place(
         board: &Board,
         player: Player,
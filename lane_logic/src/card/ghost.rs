@@ -1,6 +1,6 @@
-use crate::{Board, Direction, Index, PlaceStatus, Player, Position, PushStatus, Set};
+use crate::{Board, Direction, Index, PlaceStatus, Player, Position, Set};
 
-use super::{normal::Normal, Card};
+use super::{normal::Normal, Card, PushChain};
 
 #[derive(Debug, Clone, Default)]
 pub struct Ghost {}
@@ -10,7 +10,7 @@ impl Card for Ghost {
         board: &mut Board,
         self_index: Index,
         direction: Direction,
-        _depth: usize,
+        _chain: &PushChain,
     ) -> Set<Index> {
         let mut current_index = self_index;
         let new_position = loop {
@@ -27,19 +27,6 @@ impl Card for Ghost {
         Set::from_iter([self_index])
     }
 
-    fn can_push(
-        board: &Board,
-        self_index: Index,
-        direction: Direction,
-        depth: usize,
-    ) -> PushStatus {
-        match Normal::can_push(board, self_index, direction, depth) {
-            PushStatus::Success(0) => PushStatus::Success(0),
-            PushStatus::Success(_) => PushStatus::Success(1),
-            PushStatus::Fail => PushStatus::Success(0),
-        }
-    }
-
     fn can_place(
         board: &Board,
         player: Player,
@@ -1,6 +1,6 @@
-use crate::{Board, Direction, Index, PlaceStatus, Player, Position, PushStatus, Set};
+use crate::{Board, Direction, Index, PlaceStatus, Player, Position, Set};
 
-use super::{Card, CardData};
+use super::{Card, CardData, PushChain};
 
 #[derive(Debug, Clone, Default)]
 pub struct Normal {}
@@ -10,18 +10,14 @@ impl Card for Normal {
         board: &mut Board,
         self_index: Index,
         direction: Direction,
-        depth: usize,
+        chain: &PushChain,
     ) -> Set<Index> {
         let my_position = board[self_index].position;
         let next_position = my_position + direction;
 
-        if depth > board.number_of_cards() {
-            return Set::new();
-        }
-
         // find index of next item
         let mut moved = if let Some(next_index) = board.get_card_position(next_position) {
-            let moved = CardData::push(board, next_index, direction, depth + 1);
+            let moved = CardData::push(board, next_index, direction, chain);
 
             if moved.is_empty() {
                 return moved;
@@ -32,38 +28,14 @@ impl Card for Normal {
             Set::new()
         };
 
-        moved.insert(self_index);
-        if board[self_index].position == my_position {
-            board.move_card(self_index, next_position);
+        // a card which bounced back has already moved
+        if board[self_index].position == my_position && board.move_card(self_index, next_position) {
+            moved.insert(self_index);
         }
 
         moved
     }
 
-    fn can_push(
-        board: &Board,
-        self_index: Index,
-        direction: Direction,
-        depth: usize,
-    ) -> PushStatus {
-        let my_position = board[self_index].position;
-        let next_position = my_position + direction;
-
-        if depth > board.number_of_cards() {
-            return PushStatus::Fail;
-        }
-
-        // find index of next item
-        if let Some(next_index) = board.get_card_position(next_position) {
-            match CardData::can_push(board, next_index, direction, depth) {
-                PushStatus::Success(n) => PushStatus::Success(n + 1),
-                PushStatus::Fail => PushStatus::Fail,
-            }
-        } else {
-            PushStatus::Success(0)
-        }
-    }
-
     fn can_place(
         board: &Board,
         _player: Player,
@@ -89,7 +61,7 @@ impl Card for Normal {
     }
 }
 
-pub(crate) fn normal_placement(
+pub fn normal_placement(
     board: &mut Board,
     player: Player,
     position: Position,
@@ -97,10 +69,14 @@ pub(crate) fn normal_placement(
     card_data: CardData,
 ) -> (Index, Set<Index>) {
     let idx = board.add_card(player, position, card_data);
-    (idx, CardData::push(board, idx, direction, 0))
+    board.next_step();
+    (
+        idx,
+        CardData::push(board, idx, direction, &PushChain::default()),
+    )
 }
 
-pub(crate) fn normal_placement_rule(
+pub fn normal_placement_rule(
     board: &Board,
     position: Position,
     direction: Direction,
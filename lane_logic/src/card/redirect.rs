@@ -1,6 +1,6 @@
 use crate::Set;
 
-use super::{normal::Normal, Card, CardData};
+use super::{normal::Normal, Card, CardData, PushChain};
 
 #[derive(Debug, Clone, Default)]
 pub struct Redirect {}
@@ -10,7 +10,7 @@ impl Card for Redirect {
         board: &mut crate::Board,
         self_index: crate::Index,
         direction: crate::Direction,
-        depth: usize,
+        chain: &PushChain,
     ) -> crate::Set<crate::Index> {
         let my_position = board[self_index].position;
         let mut moved_cards = Set::new();
@@ -20,7 +20,7 @@ impl Card for Redirect {
         for push_direction in push_directions.into_iter() {
             let push_position = my_position + push_direction;
             if let Some(next_index) = board.get_card_position(push_position) {
-                let moved = CardData::push(board, next_index, push_direction, depth);
+                let moved = CardData::push(board, next_index, push_direction, chain);
 
                 if push_direction == direction && moved.is_empty() {
                     return moved;
@@ -30,23 +30,15 @@ impl Card for Redirect {
             }
         }
 
-        if board[self_index].position == my_position {
+        if board[self_index].position == my_position
+            && board.move_card(self_index, my_position + direction)
+        {
             moved_cards.insert(self_index);
-            board.move_card(self_index, my_position + direction);
         }
 
         moved_cards
     }
 
-    fn can_push(
-        board: &crate::Board,
-        self_index: crate::Index,
-        direction: crate::Direction,
-        depth: usize,
-    ) -> crate::PushStatus {
-        Normal::can_push(board, self_index, direction, depth)
-    }
-
     fn can_place(
         board: &crate::Board,
         player: crate::Player,
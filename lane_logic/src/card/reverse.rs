@@ -1,6 +1,6 @@
 use super::{
     normal::{normal_placement, normal_placement_rule, Normal},
-    Card,
+    Card, PushChain,
 };
 
 #[derive(Debug, Clone, Default)]
@@ -11,18 +11,9 @@ impl Card for Reverse {
         board: &mut crate::Board,
         self_index: crate::Index,
         direction: crate::Direction,
-        depth: usize,
+        chain: &PushChain,
     ) -> crate::Set<crate::Index> {
-        Normal::push(board, self_index, -direction, depth)
-    }
-
-    fn can_push(
-        board: &crate::Board,
-        self_index: crate::Index,
-        direction: crate::Direction,
-        depth: usize,
-    ) -> crate::PushStatus {
-        Normal::can_push(board, self_index, -direction, depth)
+        Normal::push(board, self_index, -direction, chain)
     }
 
     fn can_place(
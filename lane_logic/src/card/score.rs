@@ -1,6 +1,6 @@
-use crate::{Board, Direction, Index, PlaceStatus, Player, Position, PushStatus, Set};
+use crate::{Board, Direction, Index, PlaceStatus, Player, Position, Set};
 
-use super::{normal::Normal, Card};
+use super::{normal::Normal, Card, PushChain};
 
 #[derive(Debug, Clone, Default)]
 pub struct Score {}
@@ -10,18 +10,9 @@ impl Card for Score {
         board: &mut Board,
         self_index: Index,
         direction: Direction,
-        depth: usize,
+        chain: &PushChain,
     ) -> Set<Index> {
-        Normal::push(board, self_index, direction, depth)
-    }
-
-    fn can_push(
-        board: &Board,
-        self_index: Index,
-        direction: Direction,
-        depth: usize,
-    ) -> PushStatus {
-        Normal::can_push(board, self_index, direction, depth)
+        Normal::push(board, self_index, direction, chain)
     }
 
     fn can_place(
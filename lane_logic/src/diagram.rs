@@ -0,0 +1,327 @@
+//! Drawing boards and states as text, and building states from drawings.
+//!
+//! The board is a grid with the x coordinate of each column along the top and
+//! the y coordinate of each row down the side, north at the top. A card is a
+//! letter for its type, in upper case for player A, lower case for player B and
+//! in brackets if it belongs to nobody. A state also has whose turn it is, the
+//! hands written as in [`crate::record`] and the rules if they aren't the
+//! defaults:
+//!
+//! ```text
+//!       -1   0   1   2
+//!    0   N (S) (S)   n
+//!    1   .   .   G   .
+//! turn: B
+//! hand A: Block Normal Ghost(1)
+//! hand B: Normal Double
+//! ```
+//!
+//! The letters are `B`lock, `N`ormal, `D`ouble, `G`host, `S`core, `R`edirect
+//! and re`V`erse. Custom cards are drawn as `?` and can't be read back in.
+//! When reading, the cells only need to be separated by spaces where two
+//! letters are next to each other, and a row can leave off empty cells at the
+//! end.
+
+use core::{fmt::Display, str::FromStr};
+
+use alloc::vec::Vec;
+
+use crate::{
+    card::CardType,
+    record::{parse_held_card, parse_player, player_name, write_hand},
+    rules::{ParseRulesError, RuleSet},
+    Board, Player, Position, State,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseDiagramError {
+    /// The numbers along the top must count up one at a time
+    InvalidColumns {
+        line: usize,
+    },
+    /// A row must start with its y coordinate
+    InvalidRow {
+        line: usize,
+    },
+    /// The same y coordinate is used for two rows
+    DuplicateRow {
+        line: usize,
+    },
+    InvalidCard {
+        line: usize,
+    },
+    /// A row has more cells than there are columns
+    TooManyCells {
+        line: usize,
+    },
+    InvalidPlayer {
+        line: usize,
+    },
+    InvalidRules {
+        line: usize,
+        error: ParseRulesError,
+    },
+    MissingTurn,
+    MissingHand(Player),
+}
+
+impl Display for ParseDiagramError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseDiagramError::InvalidColumns { line } => {
+                write!(f, "line {line}: the columns should count up by one")
+            }
+            ParseDiagramError::InvalidRow { line } => {
+                write!(
+                    f,
+                    "line {line}: expected a row starting with its y coordinate"
+                )
+            }
+            ParseDiagramError::DuplicateRow { line } => {
+                write!(f, "line {line}: this row has already been given")
+            }
+            ParseDiagramError::InvalidCard { line } => write!(f, "line {line}: invalid card"),
+            ParseDiagramError::TooManyCells { line } => {
+                write!(f, "line {line}: more cells than columns")
+            }
+            ParseDiagramError::InvalidPlayer { line } => {
+                write!(f, "line {line}: invalid player")
+            }
+            ParseDiagramError::InvalidRules { line, error } => write!(f, "line {line}: {error}"),
+            ParseDiagramError::MissingTurn => write!(f, "missing whose turn it is"),
+            ParseDiagramError::MissingHand(player) => {
+                write!(f, "missing hand for player {player:?}")
+            }
+        }
+    }
+}
+
+fn letter(card: CardType) -> char {
+    match card {
+        CardType::Block => 'B',
+        CardType::Normal => 'N',
+        CardType::Double => 'D',
+        CardType::Ghost => 'G',
+        CardType::Score => 'S',
+        CardType::Redirect => 'R',
+        CardType::Reverse => 'V',
+        CardType::Custom(_) => '?',
+    }
+}
+
+fn from_letter(letter: char) -> Option<CardType> {
+    Some(match letter.to_ascii_uppercase() {
+        'B' => CardType::Block,
+        'N' => CardType::Normal,
+        'D' => CardType::Double,
+        'G' => CardType::Ghost,
+        'S' => CardType::Score,
+        'R' => CardType::Redirect,
+        'V' => CardType::Reverse,
+        _ => return None,
+    })
+}
+
+/// The width of each column, enough for the brackets around an unowned card
+/// and a space to separate it from the one before
+const CELL_WIDTH: usize = 4;
+
+impl Display for Board {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (Some(&min_x), Some(&max_x)) = (self.columns.keys().min(), self.columns.keys().max())
+        else {
+            return Ok(());
+        };
+        let (Some(&min_y), Some(&max_y)) = (self.rows.keys().min(), self.rows.keys().max()) else {
+            return Ok(());
+        };
+
+        write!(f, "{:CELL_WIDTH$}", "")?;
+        for x in min_x..=max_x {
+            write!(f, "{x:>CELL_WIDTH$}")?;
+        }
+        writeln!(f)?;
+
+        for y in min_y..=max_y {
+            write!(f, "{y:>CELL_WIDTH$}")?;
+            for x in min_x..=max_x {
+                let Some(idx) = self.get_card_position(Position((x, y).into())) else {
+                    write!(f, "{:>CELL_WIDTH$}", ".")?;
+                    continue;
+                };
+
+                let card = &self[idx];
+                let letter = letter(card.card.to_type());
+                match card.belonging_player {
+                    Some(Player::A) => write!(f, "{letter:>CELL_WIDTH$}")?,
+                    Some(Player::B) => write!(f, "{:>CELL_WIDTH$}", letter.to_ascii_lowercase())?,
+                    None => write!(f, "{:>1$}({letter})", "", CELL_WIDTH - 3)?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.board)?;
+        writeln!(f, "turn: {}", player_name(self.turn))?;
+        for player in [Player::A, Player::B] {
+            write_hand(f, player, self.player_hand(player))?;
+        }
+
+        if self.rules != RuleSet::default() {
+            writeln!(f, "rules: {}", self.rules)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the numbers along the top, returning the first column and how many
+/// columns there are.
+fn parse_columns(line: &str) -> Option<(i32, usize)> {
+    let columns = line
+        .split_whitespace()
+        .map(|x| x.parse().ok())
+        .collect::<Option<Vec<i32>>>()?;
+
+    let first = *columns.first()?;
+    columns
+        .iter()
+        .zip(first..)
+        .all(|(&x, expected)| x == expected)
+        .then_some((first, columns.len()))
+}
+
+/// A card and its owner, or `None` for an empty cell
+type Cell = Option<(CardType, Option<Player>)>;
+
+/// Reads the cells in a row, in order from the first column.
+fn parse_cells(row: &str) -> Option<Vec<Cell>> {
+    let mut cells = Vec::new();
+    let mut chars = row.chars().filter(|c| !c.is_whitespace());
+
+    while let Some(c) = chars.next() {
+        cells.push(match c {
+            '.' => None,
+            '(' => {
+                let card = from_letter(chars.next()?)?;
+                if chars.next()? != ')' {
+                    return None;
+                }
+                Some((card, None))
+            }
+            c if c.is_ascii_uppercase() => Some((from_letter(c)?, Some(Player::A))),
+            c => Some((from_letter(c)?, Some(Player::B))),
+        });
+    }
+
+    Some(cells)
+}
+
+impl FromStr for State {
+    type Err = ParseDiagramError;
+
+    /// Builds a state from a drawing like the one [`State`]'s `Display` gives.
+    /// The cards are put on the board in reading order, and the starting layout
+    /// in the rules is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = None;
+        let mut rows = Vec::new();
+        let mut cards = Vec::new();
+        let mut turn = None;
+        let mut hands = [None, None];
+        let mut rules = None;
+
+        for (line_number, line) in s.lines().enumerate() {
+            let line_number = line_number + 1;
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(player) = line.strip_prefix("turn:") {
+                turn = Some(
+                    parse_player(player)
+                        .ok_or(ParseDiagramError::InvalidPlayer { line: line_number })?,
+                );
+            } else if let Some(hand) = line.strip_prefix("hand ") {
+                let (player, held) = hand
+                    .split_once(':')
+                    .ok_or(ParseDiagramError::InvalidPlayer { line: line_number })?;
+                let player = parse_player(player)
+                    .ok_or(ParseDiagramError::InvalidPlayer { line: line_number })?;
+
+                hands[player as usize] = Some(
+                    held.split_whitespace()
+                        .map(|card| {
+                            parse_held_card(card)
+                                .ok_or(ParseDiagramError::InvalidCard { line: line_number })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                );
+            } else if let Some(rule_set) = line.strip_prefix("rules:") {
+                rules =
+                    Some(
+                        rule_set
+                            .parse()
+                            .map_err(|error| ParseDiagramError::InvalidRules {
+                                line: line_number,
+                                error,
+                            })?,
+                    );
+            } else if let Some((first_x, width)) = columns {
+                let (y, row) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+                let y: i32 = y
+                    .parse()
+                    .map_err(|_| ParseDiagramError::InvalidRow { line: line_number })?;
+                if rows.contains(&y) {
+                    return Err(ParseDiagramError::DuplicateRow { line: line_number });
+                }
+                rows.push(y);
+
+                let cells =
+                    parse_cells(row).ok_or(ParseDiagramError::InvalidCard { line: line_number })?;
+                if cells.len() > width {
+                    return Err(ParseDiagramError::TooManyCells { line: line_number });
+                }
+
+                for (x, cell) in (first_x..).zip(cells) {
+                    if let Some((card, owner)) = cell {
+                        cards.push((Position((x, y).into()), card, owner));
+                    }
+                }
+            } else {
+                columns = Some(
+                    parse_columns(line)
+                        .ok_or(ParseDiagramError::InvalidColumns { line: line_number })?,
+                );
+            }
+        }
+
+        let [player_a, player_b] = hands;
+        let player_a = player_a.ok_or(ParseDiagramError::MissingHand(Player::A))?;
+        let player_b = player_b.ok_or(ParseDiagramError::MissingHand(Player::B))?;
+        let turn = turn.ok_or(ParseDiagramError::MissingTurn)?;
+        let rules: RuleSet = rules.unwrap_or_default();
+
+        cards.sort_by_key(|(position, _, _)| (position.0.y, position.0.x));
+        let mut state = State::new(
+            player_a,
+            player_b,
+            turn,
+            RuleSet {
+                starting_layout: cards,
+                ..rules.clone()
+            },
+        );
+        state.rules = rules;
+
+        Ok(state)
+    }
+}
@@ -0,0 +1,415 @@
+//! A compact binary encoding of a [`State`], for saving to SRAM and sending
+//! over the link cable.
+//!
+//! The encoding is a version byte, a bit packed body and a CRC-16 of
+//! everything before it. The body is:
+//!
+//! * whose turn it is, as 1 bit
+//! * how many turns have been played
+//! * 1 bit for whether the rules are the default, followed by the rules as
+//!   text if they aren't
+//! * each player's hand, as the number of cards then 3 bits of card type and
+//!   the turns until the card can be used for each card
+//! * the id the next card placed will get and the number of cards on the
+//!   board, then for each card 3 bits of card type, 2 bits of owner, its
+//!   position and its id
+//!
+//! Numbers are written in groups of 3 bits with a bit saying whether another
+//! group follows, so small numbers like cooldowns take 4 bits. Cards on the
+//! board are written in order along each row, with each position written as
+//! the difference from the previous card.
+//!
+//! Only the hash of the current position is kept, so positions from before
+//! the state was saved aren't counted as repetitions after it is loaded.
+//! Custom cards can't be encoded.
+
+use core::fmt::Display;
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{
+    card::{CardType, CustomCardType},
+    rules::{ParseRulesError, RuleSet},
+    validate::ValidationReport,
+    Board, CardId, Hand, HeldCard, PlacedCard, Player, Position, State,
+};
+
+const VERSION: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// Custom cards don't have a card type number
+    CustomCard(CustomCardType),
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EncodeError::CustomCard(card) => {
+                write!(f, "the custom card {} can't be encoded", card.name())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data ended part way through the state
+    UnexpectedEnd,
+    UnsupportedVersion(u8),
+    /// The checksum doesn't match, so the data has been corrupted
+    ChecksumMismatch,
+    /// There is data left over after the state
+    TrailingData,
+    /// A number is too big to be part of a valid state
+    NumberTooLarge,
+    InvalidCardType(u8),
+    InvalidOwner,
+    InvalidRules(ParseRulesError),
+    /// Two cards are in the same position
+    OverlappingCards(Position),
+    /// Two cards have the same id, or a card's id hasn't been handed out yet
+    InvalidCardId(CardId),
+    /// The state was read, but isn't one the game could have reached
+    InvalidState(ValidationReport),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "the data ends too early"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "version {version} states are not supported")
+            }
+            DecodeError::ChecksumMismatch => write!(f, "the checksum doesn't match"),
+            DecodeError::TrailingData => write!(f, "there is data after the end of the state"),
+            DecodeError::NumberTooLarge => write!(f, "a number is too large"),
+            DecodeError::InvalidCardType(card) => write!(f, "{card} is not a card type"),
+            DecodeError::InvalidOwner => write!(f, "a card has an invalid owner"),
+            DecodeError::InvalidRules(error) => write!(f, "the rules are invalid: {error}"),
+            DecodeError::OverlappingCards(position) => {
+                write!(f, "there is more than one card at {position}")
+            }
+            DecodeError::InvalidCardId(id) => write!(f, "card id {} is invalid", id.0),
+            DecodeError::InvalidState(report) => write!(f, "{report}"),
+        }
+    }
+}
+
+fn card_code(card: CardType) -> Result<u32, EncodeError> {
+    Ok(match card {
+        CardType::Block => 0,
+        CardType::Normal => 1,
+        CardType::Double => 2,
+        CardType::Ghost => 3,
+        CardType::Score => 4,
+        CardType::Redirect => 5,
+        CardType::Reverse => 6,
+        CardType::Custom(custom) => return Err(EncodeError::CustomCard(custom)),
+    })
+}
+
+fn card_from_code(code: u32) -> Result<CardType, DecodeError> {
+    Ok(match code {
+        0 => CardType::Block,
+        1 => CardType::Normal,
+        2 => CardType::Double,
+        3 => CardType::Ghost,
+        4 => CardType::Score,
+        5 => CardType::Redirect,
+        6 => CardType::Reverse,
+        _ => return Err(DecodeError::InvalidCardType(code as u8)),
+    })
+}
+
+/// CRC-16/CCITT-FALSE
+fn checksum(data: &[u8]) -> u16 {
+    data.iter().fold(0xffff, |crc, &byte| {
+        (0..8).fold(crc ^ (u16::from(byte) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn zigzag(n: i32) -> u32 {
+    ((n << 1) ^ (n >> 31)) as u32
+}
+
+fn unzigzag(n: u32) -> i32 {
+    (n >> 1) as i32 ^ -((n & 1) as i32)
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        for i in 0..bits {
+            if self.bit == 0 {
+                self.bytes.push(0);
+            }
+            *self.bytes.last_mut().unwrap() |= (((value >> i) & 1) as u8) << self.bit;
+            self.bit = (self.bit + 1) % 8;
+        }
+    }
+
+    fn write_number(&mut self, mut value: u32) {
+        loop {
+            let more = value >= 8;
+            self.write(value & 7 | u32::from(more) << 3, 4);
+            value >>= 3;
+            if !more {
+                return;
+            }
+        }
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    bit: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: u32) -> Result<u32, DecodeError> {
+        let mut value = 0;
+        for i in 0..bits {
+            let byte = self
+                .bytes
+                .get(self.bit / 8)
+                .ok_or(DecodeError::UnexpectedEnd)?;
+            value |= u32::from((byte >> (self.bit % 8)) & 1) << i;
+            self.bit += 1;
+        }
+        Ok(value)
+    }
+
+    fn read_number(&mut self) -> Result<u32, DecodeError> {
+        let mut value = 0u32;
+        for shift in (0..32).step_by(3) {
+            let group = self.read(4)?;
+            value |= (group & 7)
+                .checked_shl(shift)
+                .filter(|shifted| shifted >> shift == group & 7)
+                .ok_or(DecodeError::NumberTooLarge)?;
+            if group & 8 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::NumberTooLarge)
+    }
+}
+
+pub fn encode(state: &State) -> Result<Vec<u8>, EncodeError> {
+    let mut writer = BitWriter {
+        bytes: alloc::vec![VERSION],
+        bit: 0,
+    };
+
+    writer.write(state.turn as u32, 1);
+    writer.write_number(state.turns_played() as u32);
+
+    if state.rules == RuleSet::default() {
+        writer.write(1, 1);
+    } else {
+        writer.write(0, 1);
+        let rules = format!("{}", state.rules);
+        writer.write_number(rules.len() as u32);
+        for byte in rules.bytes() {
+            writer.write(u32::from(byte), 8);
+        }
+    }
+
+    for hand in &state.hands {
+        writer.write_number(hand.cards.len() as u32);
+        for card in &hand.cards {
+            let (card, turns_until_usable) = match *card {
+                HeldCard::Available(card) => (card, 0),
+                HeldCard::Waiting {
+                    card,
+                    turns_until_usable,
+                } => (card, turns_until_usable),
+            };
+            writer.write(card_code(card)?, 3);
+            writer.write_number(turns_until_usable as u32);
+        }
+    }
+
+    let mut cards: Vec<_> = state.board_state().map(|(_, card)| card).collect();
+    cards.sort_by_key(|card| (card.position.0.y, card.position.0.x));
+
+    writer.write_number(state.board.next_id.0);
+    writer.write_number(cards.len() as u32);
+    let mut previous: Option<Position> = None;
+    for card in cards {
+        writer.write(card_code(card.card.to_type())?, 3);
+        writer.write(
+            match card.belonging_player {
+                None => 0,
+                Some(Player::A) => 1,
+                Some(Player::B) => 2,
+            },
+            2,
+        );
+
+        let position = card.position.0;
+        match previous {
+            None => {
+                writer.write_number(zigzag(position.x));
+                writer.write_number(zigzag(position.y));
+            }
+            Some(Position(previous)) => {
+                let dy = position.y.wrapping_sub(previous.y);
+                writer.write_number(dy as u32);
+                if dy == 0 {
+                    // cards in a row are in order, so the gap is at least 1
+                    writer
+                        .write_number((position.x.wrapping_sub(previous.x) as u32).wrapping_sub(1));
+                } else {
+                    writer.write_number(zigzag(position.x.wrapping_sub(previous.x)));
+                }
+            }
+        }
+        writer.write_number(card.id.0);
+        previous = Some(card.position);
+    }
+
+    let mut bytes = writer.bytes;
+    let crc = checksum(&bytes);
+    bytes.extend_from_slice(&crc.to_be_bytes());
+    Ok(bytes)
+}
+
+/// Reads a state written by [`encode`], which is then checked with
+/// [`State::validate`] so a decoded state is always one the game can continue.
+pub fn decode(bytes: &[u8]) -> Result<State, DecodeError> {
+    let (&version, _) = bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let (data, crc) = bytes
+        .split_last_chunk::<2>()
+        .ok_or(DecodeError::UnexpectedEnd)?;
+    if checksum(data) != u16::from_be_bytes(*crc) {
+        return Err(DecodeError::ChecksumMismatch);
+    }
+
+    let mut reader = BitReader {
+        bytes: &data[1..],
+        bit: 0,
+    };
+
+    let turn = if reader.read(1)? == 0 {
+        Player::A
+    } else {
+        Player::B
+    };
+    let turns_played = reader.read_number()? as usize;
+
+    let rules = if reader.read(1)? == 1 {
+        RuleSet::default()
+    } else {
+        let len = reader.read_number()? as usize;
+        if len > data.len() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let rules = (0..len)
+            .map(|_| reader.read(8).map(|byte| byte as u8))
+            .collect::<Result<Vec<_>, _>>()?;
+        String::from_utf8(rules)
+            .map_err(|_| DecodeError::InvalidRules(ParseRulesError::UnknownRule))?
+            .parse()
+            .map_err(DecodeError::InvalidRules)?
+    };
+
+    let mut hands = [Hand::new(Vec::new()), Hand::new(Vec::new())];
+    for hand in &mut hands {
+        let len = reader.read_number()? as usize;
+        for _ in 0..len {
+            let card = card_from_code(reader.read(3)?)?;
+            hand.cards.push(match reader.read_number()? as usize {
+                0 => HeldCard::Available(card),
+                turns_until_usable => HeldCard::Waiting {
+                    card,
+                    turns_until_usable,
+                },
+            });
+        }
+    }
+
+    let mut board = Board::new(&[]);
+    board.next_id = CardId(reader.read_number()?);
+    let len = reader.read_number()?;
+    let mut previous: Option<Position> = None;
+    for _ in 0..len {
+        let card = card_from_code(reader.read(3)?)?;
+        let belonging_player = match reader.read(2)? {
+            0 => None,
+            1 => Some(Player::A),
+            2 => Some(Player::B),
+            _ => return Err(DecodeError::InvalidOwner),
+        };
+
+        let position = match previous {
+            None => {
+                let x = unzigzag(reader.read_number()?);
+                let y = unzigzag(reader.read_number()?);
+                Position((x, y).into())
+            }
+            Some(Position(previous)) => {
+                let dy = reader.read_number()? as i32;
+                let x = if dy == 0 {
+                    previous
+                        .x
+                        .wrapping_add((reader.read_number()? as i32).wrapping_add(1))
+                } else {
+                    previous.x.wrapping_add(unzigzag(reader.read_number()?))
+                };
+                Position((x, previous.y.wrapping_add(dy)).into())
+            }
+        };
+
+        if board.get_card_position(position).is_some() {
+            return Err(DecodeError::OverlappingCards(position));
+        }
+
+        let id = CardId(reader.read_number()?);
+        if id >= board.next_id || board.by_id.contains_key(&id) {
+            return Err(DecodeError::InvalidCardId(id));
+        }
+
+        board.insert_card(PlacedCard {
+            belonging_player,
+            position,
+            card: card.to_data(),
+            id,
+        });
+        previous = Some(position);
+    }
+
+    if reader.bit.div_ceil(8) != reader.bytes.len() {
+        return Err(DecodeError::TrailingData);
+    }
+
+    let mut state = State {
+        turn,
+        board,
+        hands,
+        hash: 0,
+        rules,
+        history: Vec::new(),
+        turns_before_history: turns_played,
+    };
+    state.update_hash();
+    state.history.push(state.hash);
+    state.validate().map_err(DecodeError::InvalidState)?;
+
+    Ok(state)
+}
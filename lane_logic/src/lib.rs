@@ -7,28 +7,46 @@ use agb_fixnum::Vector2D;
 
 use agb_hashmap::{HashMap, IterOwned};
 use alloc::vec::Vec;
-use slotmap::HopSlotMap;
+use arena::Arena;
+mod arena;
+#[allow(dead_code)]
 mod rustc_hash;
+#[cfg(feature = "serde")]
+mod serialise;
+mod zobrist;
 
 extern crate alloc;
 
 pub mod card;
-
-use card::{CardData, CardType};
-
+pub mod diagram;
+pub mod encoding;
+pub mod notation;
+pub mod record;
+pub mod rng;
+pub mod rules;
+pub mod validate;
+
+use card::{CardData, CardType, PushChain};
+use rules::{RuleSet, SandwichRule};
+
+/// Why a push didn't move anything, found while making it
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum PushStatus {
-    Success(u32),
-    Fail,
+enum PushRefusal {
+    /// The card at the given index refused to move
+    Blocked(Index),
+    /// The push came back round to the card at the given index, which was
+    /// already being pushed
+    Cycle(Index),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum PlaceStatus {
+pub enum PlaceStatus {
     Success,
     Fail,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     North,
     East,
@@ -87,11 +105,23 @@ impl Neg for Direction {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Player {
     A,
     B,
 }
 
+impl Neg for Player {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Player::A => Player::B,
+            Player::B => Player::A,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Position(pub Vector2D<i32>);
 
@@ -110,20 +140,38 @@ impl Add<Direction> for Position {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlacedCard {
     pub belonging_player: Option<Player>,
     pub position: Position,
     pub card: CardData,
+    pub id: CardId,
 }
 
+/// Identifies a card for the whole game. Unlike [`Index`] this is the same in
+/// every copy of a state, including ones which have been saved and loaded
+/// again. Cards are numbered in the order they were put on the board and
+/// numbers are never reused.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CardId(pub u32);
+
 #[derive(Debug, Clone)]
 pub struct State {
     turn: Player,
     board: Board,
     hands: [Hand; 2],
+    hash: u64,
+    rules: RuleSet,
+    /// The hash after every move so far, starting with the initial state
+    history: Vec<u64>,
+    /// Moves made before the start of `history`, for states that were loaded
+    /// without their history
+    turns_before_history: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum HeldCard {
     Available(CardType),
     Waiting {
@@ -138,7 +186,18 @@ struct Hand {
 }
 
 impl Hand {
-    fn new(cards: Vec<HeldCard>) -> Self {
+    /// A card waiting for no more turns is already available, and is held as
+    /// one so that counting down and undoing it always mirror each other.
+    fn new(mut cards: Vec<HeldCard>) -> Self {
+        for card in &mut cards {
+            if let HeldCard::Waiting {
+                card: waiting,
+                turns_until_usable: 0,
+            } = *card
+            {
+                *card = HeldCard::Available(waiting);
+            }
+        }
         Hand { cards }
     }
 }
@@ -149,38 +208,204 @@ impl State {
     }
 
     pub fn can_execute_move(&self, m: &Move) -> bool {
+        self.check_move(m).is_ok()
+    }
+
+    /// Checks whether the current player may make the given move, and if not,
+    /// why. This tries the move on a copy of the state, so it agrees with
+    /// what executing the move would do.
+    pub fn check_move(&self, m: &Move) -> Result<(), MoveError> {
+        self.clone().execute_checked(m, false).map(|_| ())
+    }
+
+    /// Works out everything the move would do, including captures and the new
+    /// score, without changing the state.
+    pub fn simulate(&self, m: &Move) -> Result<MoveResult, MoveError> {
+        self.clone().try_execute_move(m)
+    }
+
+    /// The checks which can be done without making the move.
+    fn check_move_before_executing(&self, m: &Move) -> Result<(), MoveError> {
         match m {
-            Move::PlaceCard(place) => match self.hands[self.turn as usize].cards[place.card.0] {
-                HeldCard::Available(card) => {
-                    self.board
-                        .no_cards_in_direction(place.coordinate, -place.direction)
-                        && self.board.get_card_position(place.coordinate).is_none()
-                        && self
-                            .board
-                            .can_place(card, self.turn, place.coordinate, place.direction)
-                            == PlaceStatus::Success
-                }
-                HeldCard::Waiting { .. } => false,
-            },
-            Move::PushCard(push) => {
-                self.board
-                    .get_card(push.place)
-                    .map_or(false, |c| c.belonging_player == Some(self.turn))
-                    && match self.board.can_push(push.place, push.direction) {
-                        PushStatus::Success(1..) => true,
-                        PushStatus::Success(0) => false,
-                        PushStatus::Fail => false,
+            Move::PlaceCard(place) => {
+                let hand = &self.hands[self.turn as usize].cards;
+                let card = match hand.get(place.card.0) {
+                    Some(HeldCard::Available(card)) => *card,
+                    Some(HeldCard::Waiting {
+                        card,
+                        turns_until_usable,
+                    }) => {
+                        return Err(MoveError::CardWaiting {
+                            card: *card,
+                            turns_until_usable: *turns_until_usable,
+                        })
                     }
+                    None => {
+                        return Err(MoveError::HeldCardOutOfRange {
+                            index: place.card.0,
+                            hand_size: hand.len(),
+                        })
+                    }
+                };
+
+                if card == CardType::Score {
+                    return Err(MoveError::CardNotPlaceable(card));
+                }
+
+                if self.board.get_card_position(place.coordinate).is_some() {
+                    return Err(MoveError::PositionOccupied(place.coordinate));
+                }
+
+                if !self
+                    .board
+                    .no_cards_in_direction(place.coordinate, -place.direction)
+                {
+                    return Err(MoveError::NotAnEdge {
+                        position: place.coordinate,
+                        direction: place.direction,
+                    });
+                }
+
+                match self
+                    .board
+                    .can_place(card, self.turn, place.coordinate, place.direction)
+                {
+                    PlaceStatus::Success => Ok(()),
+                    PlaceStatus::Fail => Err(MoveError::NoAdjacentCard {
+                        position: place.coordinate,
+                        direction: place.direction,
+                    }),
+                }
+            }
+            Move::PushCard(PushCardMove { place: card, .. })
+            | Move::PickCard(PickCardMove { card }) => self.check_owned(*card),
+        }
+    }
+
+    fn check_owned(&self, id: CardId) -> Result<(), MoveError> {
+        let (_, card) = self.card_by_id(id).ok_or(MoveError::CardNotFound(id))?;
+        if card.belonging_player == Some(self.turn) {
+            Ok(())
+        } else {
+            Err(MoveError::NotOwned {
+                card: id,
+                owner: card.belonging_player,
+            })
+        }
+    }
+
+    /// Executes the move if it is legal, otherwise reports why it isn't and
+    /// leaves the state untouched.
+    pub fn try_execute_move(&mut self, m: &Move) -> Result<MoveResult, MoveError> {
+        let result = self.execute_checked(m, true).map(|(result, _)| result);
+        #[cfg(feature = "validate")]
+        self.validate_or_panic(m);
+        result
+    }
+
+    /// Executes the move if it is legal. Pushes are only legal if they push
+    /// something, and with [`RuleSet::connected`] moves must leave the cards in
+    /// one group, which are only known once the move has been made, so these
+    /// illegal moves are made and then undone.
+    ///
+    /// Only checking legality doesn't need the outcome, and leaving it out
+    /// means legality doesn't depend on whether the opponent can move.
+    fn execute_checked(
+        &mut self,
+        m: &Move,
+        with_outcome: bool,
+    ) -> Result<(MoveResult, UndoToken), MoveError> {
+        self.check_move_before_executing(m)?;
+
+        let (mut result, undo) = self.execute_move_inner(m, false);
+
+        if let Move::PushCard(push) = m {
+            let refusal = self.board.refusal.take();
+            let blocked = result.moved.is_empty();
+            if blocked || only_slid(&result, push.place) {
+                self.undo_move(undo);
+                let card = push.place;
+                return Err(match refusal {
+                    _ if !blocked => MoveError::NothingToPush {
+                        card,
+                        direction: push.direction,
+                    },
+                    Some(PushRefusal::Cycle(through)) => MoveError::PushCycle {
+                        card,
+                        through: self.board[through].id,
+                    },
+                    Some(PushRefusal::Blocked(blocker)) => MoveError::PushBlocked {
+                        card,
+                        blocked_by: self.board[blocker].id,
+                    },
+                    None => MoveError::PushBlocked {
+                        card,
+                        blocked_by: card,
+                    },
+                });
             }
-            Move::PickCard(pick) => self
-                .board
-                .get_card(pick.card)
-                .map_or(false, |c| c.belonging_player == Some(self.turn)),
         }
+
+        if self.rules.connected && !self.board.is_connected() {
+            self.undo_move(undo);
+            return Err(MoveError::DisconnectsBoard);
+        }
+
+        // only once the move is known to be legal, as finding the outcome
+        // looks at the moves from here
+        if with_outcome {
+            result.outcome = self.outcome_after_move(&result.score);
+        }
+
+        Ok((result, undo))
     }
 
+    /// Whether the move is legal, found by making it and taking it back.
+    fn is_legal_in_place(&mut self, m: &Move) -> bool {
+        match self.execute_checked(m, false) {
+            Ok((_, undo)) => {
+                self.undo_move(undo);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Executes the move without checking that it is legal. Panics if the move
+    /// refers to a card that isn't available, see [`State::try_execute_move`].
+    ///
+    /// The result's outcome is still worked out, which means looking for a
+    /// legal move for the next player by making and undoing moves until one
+    /// is. That is usually the first one tried, but with
+    /// [`RuleSet::connected`] or a crowded board it can take several, and
+    /// searches which make many moves pay for it at every one.
     pub fn execute_move(&mut self, m: &Move) -> MoveResult {
-        let (placed, moved, mut picked) = match m {
+        self.execute_move_with_undo(m).0
+    }
+
+    /// Executes the move like [`State::execute_move`], also returning a token
+    /// which [`State::undo_move`] can use to take the move back.
+    pub fn execute_move_with_undo(&mut self, m: &Move) -> (MoveResult, UndoToken) {
+        let executed = self.execute_move_inner(m, true);
+        #[cfg(feature = "validate")]
+        self.validate_or_panic(m);
+        executed
+    }
+
+    fn execute_move_inner(&mut self, m: &Move, with_outcome: bool) -> (MoveResult, UndoToken) {
+        self.board.movements.clear();
+        self.board.step = 0;
+        let previous_score = self.board.score();
+
+        let mut undo = UndoToken {
+            placed: None,
+            movements: Vec::new(),
+            removed: Vec::new(),
+            returned: [0, 0],
+            decremented: Vec::new(),
+        };
+
+        let (placed, moved) = match m {
             Move::PlaceCard(place) => match self.hands[self.turn as usize].cards[place.card.0] {
                 HeldCard::Available(card) => {
                     self.hands[self.turn as usize].cards.remove(place.card.0);
@@ -189,93 +414,329 @@ impl State {
                         self.board
                             .start_place(card, self.turn, place.coordinate, place.direction);
 
+                    undo.placed = Some((new_card, place.card.0, card));
+
                     (
                         alloc::vec![(
-                            new_card,
+                            self.board[new_card].id,
                             place.direction,
                             PlacedCard {
                                 belonging_player: Some(self.turn),
                                 position: place.coordinate,
-                                card: card.to_data()
+                                card: card.to_data(),
+                                id: self.board[new_card].id,
                             }
                         )],
                         moved_cards,
-                        Vec::new(),
                     )
                 }
                 HeldCard::Waiting { .. } => panic!("invalid move"),
             },
             Move::PushCard(push) => (
                 Vec::new(),
-                self.board.start_push(push.place, push.direction),
-                Vec::new(),
+                self.board
+                    .start_push(self.board.index_of(push.place), push.direction),
             ),
             Move::PickCard(pick) => {
-                let card = self.board.remove_card(pick.card);
-                (Vec::new(), Set::new(), alloc::vec![(pick.card, card)])
+                let idx = self.board.index_of(pick.card);
+                let card = self.board.remove_card(idx);
+                undo.removed.push((idx, card));
+                (Vec::new(), Set::new())
             }
         };
 
-        for card_in_hand in self.hands.iter_mut().flat_map(|x| x.cards.iter_mut()) {
-            match card_in_hand {
-                HeldCard::Available(_) => {}
-                HeldCard::Waiting {
-                    card,
-                    turns_until_usable,
-                } => {
-                    *turns_until_usable -= 1;
-                    if *turns_until_usable == 0 {
-                        *card_in_hand = HeldCard::Available(*card);
+        for (player, hand) in self.hands.iter_mut().enumerate() {
+            for (slot, card_in_hand) in hand.cards.iter_mut().enumerate() {
+                match card_in_hand {
+                    HeldCard::Available(_) => {}
+                    HeldCard::Waiting {
+                        card,
+                        turns_until_usable,
+                    } => {
+                        *turns_until_usable -= 1;
+                        if *turns_until_usable == 0 {
+                            *card_in_hand = HeldCard::Available(*card);
+                        }
+                        undo.decremented.push((player, slot));
                     }
                 }
             }
         }
 
-        let moved = moved
+        // in the order the cards first moved, so it doesn't depend on the set
+        let mut moved_in_order: Vec<(CardId, PlacedCard)> = Vec::with_capacity(moved.len());
+        for movement in &self.board.movements {
+            let card = &self.board[movement.card];
+            if moved.contains(&movement.card)
+                && !moved_in_order.iter().any(|(id, _)| *id == card.id)
+            {
+                moved_in_order.push((card.id, card.clone()));
+            }
+        }
+
+        let mut events: Vec<_> = placed
             .iter()
-            .map(|&idx| (idx, self.board[idx].clone()))
+            .map(|(_, direction, card)| BoardEvent::Placed {
+                card: card.id,
+                position: card.position,
+                direction: *direction,
+                step: 0,
+            })
+            .chain(
+                self.board
+                    .movements
+                    .iter()
+                    .map(|movement| BoardEvent::Moved {
+                        card: self.board[movement.card].id,
+                        from: movement.from,
+                        to: movement.to,
+                        step: movement.step,
+                    }),
+            )
             .collect();
 
-        let mut removed = self.board.remove_cards();
-        removed.append(&mut picked);
+        // captures happen once everything has stopped moving
+        let last_step = events.last().map_or(0, |event| event.step() + 1);
+
+        let captured = self.board.remove_cards(self.rules.capture);
+        let mut removed = Vec::with_capacity(captured.len() + 1);
+        for (idx, card, by) in captured {
+            events.push(BoardEvent::Captured {
+                card: card.id,
+                by,
+                step: last_step,
+            });
+            removed.push((card.id, card.clone()));
+            undo.removed.push((idx, card));
+        }
+
+        // the picked card was removed first but is reported last
+        if let Move::PickCard(pick) = m {
+            removed.push((pick.card, undo.removed[0].1.clone()));
+        }
 
         for (_, card) in removed.iter() {
             if let Some(player) = card.belonging_player {
-                self.hands[player as usize].cards.push(HeldCard::Waiting {
-                    card: card.card.to_type(),
-                    turns_until_usable: 1,
+                events.push(BoardEvent::ReturnedToHand {
+                    card: card.id,
+                    player,
+                    step: last_step,
                 });
+
+                let card = card.card.to_type();
+                self.hands[player as usize]
+                    .cards
+                    .push(match self.rules.cooldown(card) {
+                        0 => HeldCard::Available(card),
+                        turns_until_usable => HeldCard::Waiting {
+                            card,
+                            turns_until_usable,
+                        },
+                    });
+                undo.returned[player as usize] += 1;
             }
         }
 
+        undo.movements = core::mem::take(&mut self.board.movements);
+
         let score = self.scores();
+        for player in [Player::A, Player::B] {
+            if score.player(player) != previous_score[player as usize] {
+                events.push(BoardEvent::ScoreChanged {
+                    player,
+                    from: previous_score[player as usize],
+                    to: score.player(player),
+                    step: last_step,
+                });
+            }
+        }
 
-        let winner = match (score.player(Player::A) >= 4, score.player(Player::B) >= 4) {
-            (true, false) => Some(Player::A),
-            (false, true) => Some(Player::B),
-            (_, _) => None,
-        };
+        self.turn = -self.turn;
+        self.update_hash();
+        self.history.push(self.hash);
 
-        self.turn = match self.turn {
-            Player::A => Player::B,
-            Player::B => Player::A,
+        let outcome = if with_outcome {
+            self.outcome_after_move(&score)
+        } else {
+            GameOutcome::Ongoing
         };
 
-        MoveResult {
-            placed,
-            moved,
-            removed,
-            winner,
-            score,
+        (
+            MoveResult {
+                placed,
+                moved: moved_in_order,
+                removed,
+                events,
+                outcome,
+                score,
+            },
+            undo,
+        )
+    }
+
+    /// Takes back a move made with [`State::execute_move_with_undo`], restoring
+    /// the state to exactly how it was, including the indices of the cards.
+    /// Moves must be undone in the reverse order to how they were made.
+    pub fn undo_move(&mut self, undo: UndoToken) {
+        self.turn = -self.turn;
+        self.history.pop();
+
+        for (player, &returned) in undo.returned.iter().enumerate() {
+            let cards = &mut self.hands[player].cards;
+            cards.truncate(cards.len() - returned);
+        }
+
+        for (idx, card) in undo.removed.into_iter().rev() {
+            self.board.unremove_card(idx, card);
         }
+
+        for (player, slot) in undo.decremented {
+            let card_in_hand = &mut self.hands[player].cards[slot];
+            *card_in_hand = match *card_in_hand {
+                HeldCard::Available(card) => HeldCard::Waiting {
+                    card,
+                    turns_until_usable: 1,
+                },
+                HeldCard::Waiting {
+                    card,
+                    turns_until_usable,
+                } => HeldCard::Waiting {
+                    card,
+                    turns_until_usable: turns_until_usable + 1,
+                },
+            };
+        }
+
+        for movement in undo.movements.into_iter().rev() {
+            self.board.set_position(movement.card, movement.from);
+        }
+
+        if let Some((idx, slot, card)) = undo.placed {
+            self.board.uninsert_card(idx);
+            self.hands[self.turn as usize]
+                .cards
+                .insert(slot, HeldCard::Available(card));
+        }
+
+        self.update_hash();
+        #[cfg(feature = "validate")]
+        self.validate_or_panic(&"undoing a move");
+    }
+
+    fn outcome_after_move(&mut self, score: &Score) -> GameOutcome {
+        let score_to_win = self.rules.score_to_win;
+        match (
+            score.player(Player::A) >= score_to_win,
+            score.player(Player::B) >= score_to_win,
+        ) {
+            (true, false) => return GameOutcome::Win(Player::A),
+            (false, true) => return GameOutcome::Win(Player::B),
+            (_, _) => {}
+        }
+
+        if !self.has_legal_move_in_place() {
+            return GameOutcome::Draw(DrawReason::NoLegalMoves(self.turn));
+        }
+
+        if self.repetitions() >= 3 {
+            return GameOutcome::Draw(DrawReason::Repetition);
+        }
+
+        if self
+            .rules
+            .max_turns
+            .is_some_and(|max_turns| self.turns_played() >= max_turns)
+        {
+            return GameOutcome::Draw(DrawReason::TurnLimit);
+        }
+
+        GameOutcome::Ongoing
+    }
+
+    /// The number of moves made since the start of the game.
+    pub fn turns_played(&self) -> usize {
+        self.turns_before_history + self.history.len() - 1
+    }
+
+    /// How many times the current position has occurred in this game,
+    /// including now.
+    pub fn repetitions(&self) -> usize {
+        self.history
+            .iter()
+            .filter(|&&hash| hash == self.hash)
+            .count()
+    }
+
+    /// Whether the current player can make any move at all.
+    pub fn has_legal_move(&self) -> bool {
+        self.clone().has_legal_move_in_place()
+    }
+
+    fn has_legal_move_in_place(&mut self) -> bool {
+        let candidates: Vec<_> = self.candidate_moves().collect();
+        candidates.iter().any(|m| self.is_legal_in_place(m))
+    }
+
+    /// A hash of everything that affects how the game continues from here: the
+    /// cards on the board relative to each other, the hands and whose turn it
+    /// is. It doesn't depend on card indices, so the same position reached by
+    /// different moves has the same hash.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Brings the hash up to date after the board, hands or turn have changed.
+    /// The board keeps its part of the hash up to date as cards move, the
+    /// hands are small enough to just rehash.
+    fn update_hash(&mut self) {
+        self.board.normalise_hash();
+
+        let hands = [Player::A, Player::B]
+            .into_iter()
+            .flat_map(|player| {
+                self.hands[player as usize]
+                    .cards
+                    .iter()
+                    .map(move |card| zobrist::held_card_key(player, card))
+            })
+            .fold(0u64, u64::wrapping_add);
+
+        let turn = match self.turn {
+            Player::A => 0,
+            Player::B => zobrist::PLAYER_B_TO_MOVE,
+        };
+
+        self.hash = self.board.hash ^ hands ^ turn;
     }
 
-    pub fn new(player_a: Vec<HeldCard>, player_b: Vec<HeldCard>, starting_player: Player) -> Self {
-        State {
+    /// Starts a game. A held card waiting for no turns is taken to be
+    /// available.
+    pub fn new(
+        player_a: Vec<HeldCard>,
+        player_b: Vec<HeldCard>,
+        starting_player: Player,
+        rules: RuleSet,
+    ) -> Self {
+        let mut state = State {
             turn: starting_player,
-            board: Board::new(),
+            board: Board::new(&rules.starting_layout),
             hands: [Hand::new(player_a), Hand::new(player_b)],
-        }
+            hash: 0,
+            rules,
+            history: Vec::new(),
+            turns_before_history: 0,
+        };
+        state.update_hash();
+        state.history.push(state.hash);
+        state
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
     }
 
     pub fn player_hand(&self, player: Player) -> &[HeldCard] {
@@ -296,6 +757,13 @@ impl State {
         self.board.get_card(idx)
     }
 
+    pub fn card_by_id(&self, id: CardId) -> Option<(Index, &PlacedCard)> {
+        self.board
+            .by_id
+            .get(&id)
+            .map(|&idx| (idx, &self.board[idx]))
+    }
+
     pub fn turn_hand(&self) -> &[HeldCard] {
         self.player_hand(self.turn())
     }
@@ -306,54 +774,14 @@ impl State {
         }
     }
 
+    /// Every move the current player could make. Moves which are equivalent,
+    /// such as placing either of two cards of the same type in the same place,
+    /// only appear once.
     pub fn enumerate_possible_moves(&self) -> Vec<Move> {
-        let mut moves = Vec::new();
-
-        // create list of positions that is valid to place
-        let mut possible = Vec::new();
-
-        for (_idx, card) in self.board.positions.iter() {
-            for direction in DIRECTIONS {
-                let desired_spot = card.position + direction;
-                if self.board.no_cards_in_direction(card.position, direction) {
-                    possible.push((desired_spot, -direction));
-                }
-            }
-        }
-
-        // go over the hand of the current player
-        for (idx, card) in self.turn_hand().iter().enumerate() {
-            if let HeldCard::Available(_card) = card {
-                for (position, direction) in possible.iter().copied() {
-                    moves.push(Move::PlaceCard(PlaceCardMove {
-                        direction,
-                        coordinate: position,
-                        card: HeldCardIndex(idx),
-                    }))
-                }
-            }
-        }
-
-        // all possible pushing moves
-        for (idx, card) in self.board.positions.iter() {
-            if card.belonging_player != Some(self.turn()) {
-                continue;
-            }
-
-            moves.push(Move::PickCard(PickCardMove { card: Index(idx) }));
-
-            for direction in DIRECTIONS {
-                let m = Move::PushCard(PushCardMove {
-                    place: Index(idx),
-                    direction,
-                });
-                if self.can_execute_move(&m) {
-                    moves.push(m);
-                }
-            }
-        }
-
-        moves
+        let mut scratch = self.clone();
+        self.candidate_moves()
+            .filter(|m| scratch.is_legal_in_place(m))
+            .collect()
     }
 
     pub async fn enumerate_possible_moves_async<F, Fut>(&self, defer: F) -> Vec<Move>
@@ -362,7 +790,20 @@ impl State {
         Fut: Future<Output = ()>,
     {
         let mut moves = Vec::new();
+        let mut scratch = self.clone();
+
+        for m in self.candidate_moves() {
+            if scratch.is_legal_in_place(&m) {
+                moves.push(m);
+            }
+            defer().await;
+        }
+
+        moves
+    }
 
+    /// Every move worth checking for legality, without duplicates.
+    fn candidate_moves(&self) -> impl Iterator<Item = Move> + '_ {
         // create list of positions that is valid to place
         let mut possible = Vec::new();
 
@@ -372,56 +813,131 @@ impl State {
                 if self.board.no_cards_in_direction(card.position, direction) {
                     possible.push((desired_spot, -direction));
                 }
-                defer().await;
             }
         }
 
-        // go over the hand of the current player
+        // only the first card of each type in the hand, the others would do the same thing
+        let mut placeable: Vec<(usize, CardType)> = Vec::new();
         for (idx, card) in self.turn_hand().iter().enumerate() {
-            if let HeldCard::Available(_card) = card {
-                for (position, direction) in possible.iter().copied() {
-                    moves.push(Move::PlaceCard(PlaceCardMove {
-                        direction,
-                        coordinate: position,
-                        card: HeldCardIndex(idx),
-                    }));
-                    defer().await;
+            if let HeldCard::Available(card) = *card {
+                if placeable.iter().all(|&(_, c)| c != card) {
+                    placeable.push((idx, card));
                 }
             }
         }
-        defer().await;
 
-        // all possible pushing moves
-        for (idx, card) in self.board.positions.iter() {
-            if card.belonging_player != Some(self.turn()) {
-                continue;
+        let place_moves = placeable.into_iter().flat_map(move |(idx, _)| {
+            possible
+                .clone()
+                .into_iter()
+                .map(move |(position, direction)| {
+                    Move::PlaceCard(PlaceCardMove {
+                        direction,
+                        coordinate: position,
+                        card: HeldCardIndex(idx),
+                    })
+                })
+        });
+
+        // all possible picking and pushing moves
+        let board_moves = self
+            .board
+            .positions
+            .iter()
+            .filter(|(_, card)| card.belonging_player == Some(self.turn()))
+            .flat_map(|(_, card)| {
+                let id = card.id;
+                core::iter::once(Move::PickCard(PickCardMove { card: id })).chain(
+                    DIRECTIONS.into_iter().map(move |direction| {
+                        Move::PushCard(PushCardMove {
+                            place: id,
+                            direction,
+                        })
+                    }),
+                )
+            });
+
+        place_moves.chain(board_moves)
+    }
+}
+
+/// Whether a push only slid the pushed card along empty board, one space at a
+/// time, without pushing or jumping over any other card.
+fn only_slid(result: &MoveResult, card: CardId) -> bool {
+    result.moved.iter().all(|&(id, _)| id == card)
+        && result.events.iter().all(|event| match event {
+            BoardEvent::Moved { from, to, .. } => {
+                let step = to.0 - from.0;
+                step.x.abs() + step.y.abs() == 1
             }
+            _ => true,
+        })
+}
 
-            defer().await;
+/// The cards on the board. Card behaviours are given access to this to move
+/// cards around when they are pushed or placed. They can look at any card by
+/// indexing the board, but only change it through [`Board::move_card`] and
+/// [`Board::add_card`], which keep the lookups by position and id and the hash
+/// up to date.
+#[derive(Debug, Clone)]
+pub struct Board {
+    positions: Arena<PlacedCard>,
+    by_position: HashMap<Position, Index>,
+    /// The x coordinates of the cards in each row, keyed by y
+    rows: HashMap<i32, Line>,
+    /// The y coordinates of the cards in each column, keyed by x
+    columns: HashMap<i32, Line>,
+    /// Every time a card moved during the current move, in the order the
+    /// moves happened
+    movements: Vec<Movement>,
+    /// The step of the current move that pushes are happening in, see
+    /// [`Board::next_step`]
+    step: usize,
+    /// The xor of the zobrist keys of every card, relative to `hash_origin`
+    hash: u64,
+    hash_origin: Vector2D<i32>,
+    by_id: HashMap<CardId, Index>,
+    /// The id the next card put on the board will get
+    next_id: CardId,
+    /// The first card to refuse to move during the current push
+    refusal: Option<PushRefusal>,
+}
 
-            moves.push(Move::PickCard(PickCardMove { card: Index(idx) }));
+/// The coordinates of the cards along a row or column, kept sorted so the
+/// extents of the line are at either end.
+#[derive(Debug, Clone, Default)]
+struct Line {
+    coordinates: Vec<i32>,
+}
 
-            for direction in DIRECTIONS {
-                let m = Move::PushCard(PushCardMove {
-                    place: Index(idx),
-                    direction,
-                });
-                if self.can_execute_move(&m) {
-                    moves.push(m);
-                }
-                defer().await;
-            }
+impl Line {
+    fn insert(&mut self, coordinate: i32) {
+        let at = self.coordinates.partition_point(|&c| c < coordinate);
+        self.coordinates.insert(at, coordinate);
+    }
+
+    fn remove(&mut self, coordinate: i32) {
+        if let Ok(at) = self.coordinates.binary_search(&coordinate) {
+            self.coordinates.remove(at);
         }
+    }
 
-        moves
+    fn is_empty(&self) -> bool {
+        self.coordinates.is_empty()
     }
-}
 
-#[derive(Debug, Clone)]
-struct Board {
-    positions: HopSlotMap<slotmap::DefaultKey, PlacedCard>,
+    fn min(&self) -> Option<i32> {
+        self.coordinates.first().copied()
+    }
+
+    fn max(&self) -> Option<i32> {
+        self.coordinates.last().copied()
+    }
 }
 
+/// Identifies a card on the board. An index only means something to the board
+/// it came from and isn't saved, use the card's [`CardId`] to refer to it
+/// anywhere else.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Index(slotmap::DefaultKey);
 
@@ -432,21 +948,157 @@ impl Index {
 }
 
 impl Board {
-    fn new() -> Self {
-        let mut pos = HopSlotMap::new();
+    fn new(layout: &[(Position, CardType, Option<Player>)]) -> Self {
+        let mut board = Self {
+            positions: Arena::new(),
+            by_position: HashMap::new(),
+            rows: HashMap::new(),
+            columns: HashMap::new(),
+            movements: Vec::new(),
+            step: 0,
+            hash: 0,
+            hash_origin: (0, 0).into(),
+            by_id: HashMap::new(),
+            next_id: CardId(0),
+            refusal: None,
+        };
 
-        pos.insert(PlacedCard {
-            belonging_player: None,
-            position: Position((0, 0).into()),
-            card: CardType::Score.to_data(),
-        });
-        pos.insert(PlacedCard {
-            belonging_player: None,
-            position: Position((1, 0).into()),
-            card: CardType::Score.to_data(),
-        });
+        for &(position, card, belonging_player) in layout {
+            let id = board.next_card_id();
+            board.insert_card(PlacedCard {
+                belonging_player,
+                position,
+                card: card.to_data(),
+                id,
+            });
+        }
+
+        board
+    }
+
+    /// Rebuilds a board from its cards, returning `None` if two cards share an
+    /// id or position, or a card has an id which hasn't been handed out yet.
+    #[cfg(feature = "serde")]
+    fn with_cards(cards: impl IntoIterator<Item = PlacedCard>, next_id: CardId) -> Option<Self> {
+        let mut board = Self::new(&[]);
+        board.next_id = next_id;
+
+        for card in cards {
+            if board.get_card_position(card.position).is_some()
+                || board.by_id.contains_key(&card.id)
+                || card.id >= next_id
+            {
+                return None;
+            }
+            board.insert_card(card);
+        }
 
-        Self { positions: pos }
+        Some(board)
+    }
+
+    fn card_key(&self, idx: Index, position: Position) -> u64 {
+        let card = &self[idx];
+        zobrist::card_key(
+            position.0 - self.hash_origin,
+            card.card.to_type(),
+            card.belonging_player,
+        )
+    }
+
+    /// The top left corner of the smallest rectangle containing every card.
+    fn top_left(&self) -> Vector2D<i32> {
+        let x = self.columns.keys().copied().min().unwrap_or(0);
+        let y = self.rows.keys().copied().min().unwrap_or(0);
+        (x, y).into()
+    }
+
+    /// Makes the hash relative to the top left of the board again, so that the
+    /// same arrangement of cards hashes the same wherever it is.
+    fn normalise_hash(&mut self) {
+        let top_left = self.top_left();
+        if top_left == self.hash_origin {
+            return;
+        }
+
+        self.hash_origin = top_left;
+        self.hash = self
+            .positions
+            .iter()
+            .map(|(idx, card)| self.card_key(Index(idx), card.position))
+            .fold(0, |hash, key| hash ^ key);
+    }
+
+    fn index_position(&mut self, idx: Index, position: Position) {
+        self.hash ^= self.card_key(idx, position);
+        self.by_position.insert(position, idx);
+        self.rows
+            .entry(position.0.y)
+            .or_default()
+            .insert(position.0.x);
+        self.columns
+            .entry(position.0.x)
+            .or_default()
+            .insert(position.0.y);
+    }
+
+    fn unindex_position(&mut self, idx: Index, position: Position) {
+        self.hash ^= self.card_key(idx, position);
+        if self.by_position.get(&position) == Some(&idx) {
+            self.by_position.remove(&position);
+        }
+
+        for (lines, line, coordinate) in [
+            (&mut self.rows, position.0.y, position.0.x),
+            (&mut self.columns, position.0.x, position.0.y),
+        ] {
+            if let Some(entry) = lines.get_mut(&line) {
+                entry.remove(coordinate);
+                if entry.is_empty() {
+                    lines.remove(&line);
+                }
+            }
+        }
+    }
+
+    fn next_card_id(&mut self) -> CardId {
+        let id = self.next_id;
+        self.next_id.0 += 1;
+        id
+    }
+
+    fn insert_card(&mut self, card: PlacedCard) -> Index {
+        let position = card.position;
+        let id = card.id;
+        let idx = Index(self.positions.insert(card));
+        self.index_position(idx, position);
+        self.by_id.insert(id, idx);
+        idx
+    }
+
+    /// Puts a card back under the index it was removed from, see [`Arena::unremove`].
+    fn unremove_card(&mut self, idx: Index, card: PlacedCard) {
+        let position = card.position;
+        self.by_id.insert(card.id, idx);
+        self.positions.unremove(idx.0, card);
+        self.index_position(idx, position);
+    }
+
+    /// Takes back the most recent placement of a card, see [`Arena::uninsert`].
+    /// This also hands out its id again.
+    fn uninsert_card(&mut self, idx: Index) {
+        let position = self[idx].position;
+        self.unindex_position(idx, position);
+        if let Some(card) = self.positions.uninsert(idx.0) {
+            self.by_id.remove(&card.id);
+            self.next_id = card.id;
+        }
+    }
+
+    /// Moves a card without recording it in `movements`, for undoing moves.
+    fn set_position(&mut self, idx: Index, position: Position) {
+        let previous = core::mem::replace(&mut self.card_mut(idx).position, position);
+        self.unindex_position(idx, previous);
+        self.index_position(idx, position);
     }
 
     fn score(&self) -> [usize; 2] {
@@ -483,10 +1135,17 @@ impl Board {
     }
 
     fn start_push(&mut self, idx: Index, direction: Direction) -> Set<Index> {
-        CardData::push(self, idx, direction, 0)
+        self.refusal = None;
+        CardData::push(self, idx, direction, &PushChain::default())
     }
 
-    fn number_of_cards(&self) -> usize {
+    /// Remembers why a push failed, unless a card further along the chain
+    /// has already refused, as that is what blocked the rest.
+    fn refuse(&mut self, refusal: PushRefusal) {
+        self.refusal.get_or_insert(refusal);
+    }
+
+    pub fn number_of_cards(&self) -> usize {
         self.positions.len()
     }
 
@@ -500,10 +1159,6 @@ impl Board {
         CardData::place(self, card, player, position, direction)
     }
 
-    fn can_push(&self, idx: Index, direction: Direction) -> PushStatus {
-        CardData::can_push(self, idx, direction, 0)
-    }
-
     fn can_place(
         &self,
         card: CardType,
@@ -514,41 +1169,75 @@ impl Board {
         CardData::can_place(self, card, player, position, direction)
     }
 
-    fn get_card(&self, idx: Index) -> Option<&PlacedCard> {
+    pub fn get_card(&self, idx: Index) -> Option<&PlacedCard> {
         self.positions.get(idx.0)
     }
 
-    fn get_card_position(&self, position: Position) -> Option<Index> {
-        self.positions
-            .iter()
-            .find(|x| x.1.position == position)
-            .map(|x| Index(x.0))
+    /// The card at the index, to change in ways the board keeps track of.
+    /// Only the board itself may change where a card is or its id, as the
+    /// lookups by position and id and the hash depend on them.
+    pub(crate) fn card_mut(&mut self, idx: Index) -> &mut PlacedCard {
+        &mut self.positions[idx.0]
+    }
+
+    /// The index of the card with the given id, which must be on the board
+    fn index_of(&self, id: CardId) -> Index {
+        *self.by_id.get(&id).expect("invalid move")
+    }
+
+    /// The card at the given position, if there is one
+    pub fn get_card_position(&self, position: Position) -> Option<Index> {
+        self.by_position.get(&position).copied()
+    }
+
+    /// Moves the card if `next_position` is empty, returning whether it moved.
+    pub fn move_card(&mut self, card: Index, next_position: Position) -> bool {
+        if self.get_card_position(next_position).is_some() {
+            return false;
+        }
+
+        let previous = self[card].position;
+        self.set_position(card, next_position);
+        self.movements.push(Movement {
+            card,
+            from: previous,
+            to: next_position,
+            step: self.step,
+        });
+        true
     }
 
-    fn move_card(&mut self, card: Index, next_position: Position) {
-        self[card].position = next_position;
+    /// Starts a new step of the move. Cards moved after this are shown moving
+    /// after the cards moved before it, rather than at the same time.
+    pub fn next_step(&mut self) {
+        self.step += 1;
     }
 
     fn remove_card(&mut self, idx: Index) -> PlacedCard {
-        self.positions.remove(idx.0).unwrap()
+        self.unindex_position(idx, self[idx].position);
+        let card = self.positions.remove(idx.0).unwrap();
+        self.by_id.remove(&card.id);
+        card
     }
 
-    fn add_card(&mut self, owner: Player, position: Position, card: CardData) -> Index {
-        let idx = self.positions.insert(PlacedCard {
+    pub fn add_card(&mut self, owner: Player, position: Position, card: CardData) -> Index {
+        let id = self.next_card_id();
+        self.insert_card(PlacedCard {
             belonging_player: Some(owner),
             position,
             card,
-        });
-        Index(idx)
+            id,
+        })
     }
 
-    fn should_card_be_removed(&self, card_idx: Index) -> bool {
+    /// The cards capturing the given card, or nothing if it isn't captured.
+    fn captured_by(&self, card_idx: Index, capture: SandwichRule) -> Vec<CardId> {
         let my_player = self.get_card(card_idx).unwrap().belonging_player;
         let position = self[card_idx].position;
 
         if my_player.is_none() {
             // don't kill cards not owned by players (probably scoring cards)
-            return false;
+            return Vec::new();
         }
 
         let outer_cards = [
@@ -558,56 +1247,98 @@ impl Board {
             self.get_card_position(position + Direction::West),
         ]
         .map(|v| {
-            v.map_or(false, |idx| {
-                let card = self.get_card(idx).unwrap().belonging_player;
-                card.is_some() && card != my_player
+            v.map(|idx| self.get_card(idx).unwrap()).filter(|card| {
+                card.belonging_player.is_some() && card.belonging_player != my_player
             })
         });
 
-        (outer_cards[Direction::North as usize] && outer_cards[Direction::South as usize])
-            || (outer_cards[Direction::East as usize] && outer_cards[Direction::West as usize])
+        let axis =
+            |a: Direction, b: Direction| match (&outer_cards[a as usize], &outer_cards[b as usize])
+            {
+                (Some(a), Some(b)) => Some([a.id, b.id]),
+                _ => None,
+            };
+
+        let vertical = axis(Direction::North, Direction::South);
+        let horizontal = axis(Direction::East, Direction::West);
+
+        let captured = match capture {
+            SandwichRule::EitherAxis => vertical.is_some() || horizontal.is_some(),
+            SandwichRule::BothAxes => vertical.is_some() && horizontal.is_some(),
+        };
+
+        if captured {
+            vertical.into_iter().chain(horizontal).flatten().collect()
+        } else {
+            Vec::new()
+        }
     }
 
-    fn remove_cards(&mut self) -> Vec<(Index, PlacedCard)> {
+    /// Takes captured cards off the board, along with the cards which captured
+    /// them. They are removed in reading order, top to bottom and then left to
+    /// right, so they go back to the hands in the same order however the board
+    /// was built up.
+    fn remove_cards(&mut self, capture: SandwichRule) -> Vec<(Index, PlacedCard, Vec<CardId>)> {
         let mut removed = Vec::new();
-        for (idx, _) in self.positions.iter() {
-            if self.should_card_be_removed(Index(idx)) {
-                removed.push(Index(idx));
+        for (idx, card) in self.positions.iter() {
+            let by = self.captured_by(Index(idx), capture);
+            if !by.is_empty() {
+                removed.push((card.position, Index(idx), by));
             }
         }
+        removed.sort_by_key(|(position, _, _)| (position.0.y, position.0.x));
         removed
-            .iter()
-            .map(|&idx| (idx, self.remove_card(idx)))
+            .into_iter()
+            .map(|(_, idx, by)| (idx, self.remove_card(idx), by))
             .collect()
     }
 
-    fn no_cards_in_direction(&self, position: Position, direction: Direction) -> bool {
-        for (_, card) in self.positions.iter() {
-            match direction {
-                Direction::North => {
-                    if card.position.0.x == position.0.x && card.position.0.y < position.0.y {
-                        return false;
-                    }
-                }
-                Direction::East => {
-                    if card.position.0.y == position.0.y && card.position.0.x > position.0.x {
-                        return false;
-                    }
-                }
-                Direction::South => {
-                    if card.position.0.x == position.0.x && card.position.0.y > position.0.y {
-                        return false;
-                    }
-                }
-                Direction::West => {
-                    if card.position.0.y == position.0.y && card.position.0.x < position.0.x {
-                        return false;
-                    }
+    /// Whether every card can be reached from every other card by stepping
+    /// between cards next to each other.
+    fn is_connected(&self) -> bool {
+        let Some((_, first)) = self.positions.iter().next() else {
+            return true;
+        };
+
+        let mut reached = Set::from_iter([first.position]);
+        let mut to_visit = alloc::vec![first.position];
+        while let Some(position) = to_visit.pop() {
+            for direction in DIRECTIONS {
+                let next = position + direction;
+                if self.get_card_position(next).is_some() && !reached.contains(&next) {
+                    reached.insert(next);
+                    to_visit.push(next);
                 }
             }
         }
 
-        true
+        reached.len() == self.number_of_cards()
+    }
+
+    fn no_cards_in_direction(&self, position: Position, direction: Direction) -> bool {
+        let Vector2D { x, y } = position.0;
+        match direction {
+            Direction::North => self
+                .columns
+                .get(&x)
+                .and_then(Line::min)
+                .is_none_or(|min| min >= y),
+            Direction::East => self
+                .rows
+                .get(&y)
+                .and_then(Line::max)
+                .is_none_or(|max| max <= x),
+            Direction::South => self
+                .columns
+                .get(&x)
+                .and_then(Line::max)
+                .is_none_or(|max| max <= y),
+            Direction::West => self
+                .rows
+                .get(&y)
+                .and_then(Line::min)
+                .is_none_or(|min| min >= x),
+        }
     }
 }
 
@@ -619,45 +1350,246 @@ impl core::ops::Index<Index> for Board {
     }
 }
 
-impl core::ops::IndexMut<Index> for Board {
-    fn index_mut(&mut self, index: Index) -> &mut Self::Output {
-        &mut self.positions[index.0]
-    }
-}
-
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeldCardIndex(pub usize);
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlaceCardMove {
     pub direction: Direction,
     pub coordinate: Position,
     pub card: HeldCardIndex,
 }
 
+/// Pushes one of the current player's cards. Moves refer to cards on the
+/// board by [`CardId`], so a move means the same thing in any copy of the
+/// state, including one that was saved and loaded again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PushCardMove {
-    pub place: Index,
+    pub place: CardId,
     pub direction: Direction,
 }
 
+/// Takes one of the current player's cards off the board and back into their
+/// hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PickCardMove {
-    pub card: Index,
+    pub card: CardId,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Move {
     PlaceCard(PlaceCardMove),
     PushCard(PushCardMove),
     PickCard(PickCardMove),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveError {
+    /// The held card index is past the end of the player's hand
+    HeldCardOutOfRange { index: usize, hand_size: usize },
+    /// The held card was returned to the hand recently and can't be played yet
+    CardWaiting {
+        card: CardType,
+        turns_until_usable: usize,
+    },
+    /// Cards of this type are placed by the game, never by a player
+    CardNotPlaceable(CardType),
+    /// There is already a card where the new card would be placed
+    PositionOccupied(Position),
+    /// Cards can only be placed at the end of a lane, with nothing behind them
+    NotAnEdge {
+        position: Position,
+        direction: Direction,
+    },
+    /// There is no card for the placed card to push into
+    NoAdjacentCard {
+        position: Position,
+        direction: Direction,
+    },
+    /// There is no card with this id on the board
+    CardNotFound(CardId),
+    /// The card belongs to the other player, or to nobody
+    NotOwned { card: CardId, owner: Option<Player> },
+    /// Pushing needs a card in front to push into
+    NothingToPush { card: CardId, direction: Direction },
+    /// A card in the push chain refused to move
+    PushBlocked { card: CardId, blocked_by: CardId },
+    /// The push chain leads back round to a card which is already being pushed
+    PushCycle { card: CardId, through: CardId },
+    /// The rules need the cards to stay in one group, and this move would split them
+    DisconnectsBoard,
+}
+
+impl core::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MoveError::HeldCardOutOfRange { index, hand_size } => {
+                write!(f, "there is no card {index} in a hand of {hand_size} cards")
+            }
+            MoveError::CardWaiting {
+                card,
+                turns_until_usable,
+            } => write!(
+                f,
+                "{card:?} can't be played for another {turns_until_usable} turn(s)"
+            ),
+            MoveError::CardNotPlaceable(card) => write!(f, "{card:?} cards can't be placed"),
+            MoveError::PositionOccupied(position) => {
+                write!(f, "{position} is already occupied")
+            }
+            MoveError::NotAnEdge {
+                position,
+                direction,
+            } => write!(
+                f,
+                "{position} is not at the edge of a lane facing {direction:?}"
+            ),
+            MoveError::NoAdjacentCard {
+                position,
+                direction,
+            } => write!(f, "there is no card to push {direction:?} of {position}"),
+            MoveError::CardNotFound(_) => write!(f, "that card is not on the board"),
+            MoveError::NotOwned { owner: None, .. } => write!(f, "that card belongs to nobody"),
+            MoveError::NotOwned {
+                owner: Some(owner), ..
+            } => write!(f, "that card belongs to player {owner:?}"),
+            MoveError::NothingToPush { direction, .. } => {
+                write!(f, "there is nothing to push {direction:?}")
+            }
+            MoveError::PushBlocked { .. } => write!(f, "the push is blocked"),
+            MoveError::PushCycle { .. } => write!(f, "the push goes round in a circle"),
+            MoveError::DisconnectsBoard => write!(f, "the move splits the board in two"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MoveResult {
-    pub placed: Vec<(Index, Direction, PlacedCard)>,
-    pub moved: Vec<(Index, PlacedCard)>,
-    pub removed: Vec<(Index, PlacedCard)>,
-    pub winner: Option<Player>,
+    pub placed: Vec<(CardId, Direction, PlacedCard)>,
+    /// The cards which moved, in the order they first moved
+    pub moved: Vec<(CardId, PlacedCard)>,
+    pub removed: Vec<(CardId, PlacedCard)>,
+    /// Everything that happened during the move, in order
+    pub events: Vec<BoardEvent>,
+    pub outcome: GameOutcome,
     pub score: Score,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameOutcome {
+    Ongoing,
+    Win(Player),
+    Draw(DrawReason),
+}
+
+impl GameOutcome {
+    pub fn is_over(self) -> bool {
+        self != GameOutcome::Ongoing
+    }
+
+    pub fn winner(self) -> Option<Player> {
+        match self {
+            GameOutcome::Win(player) => Some(player),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawReason {
+    /// The player whose turn it is can't make any move
+    NoLegalMoves(Player),
+    /// The same position has occurred three times
+    Repetition,
+    /// The game reached the rule set's turn limit
+    TurnLimit,
+}
+
+#[derive(Debug, Clone)]
+struct Movement {
+    card: Index,
+    from: Position,
+    to: Position,
+    step: usize,
+}
+
+/// Something that happened during a move, in the order they happened. Events
+/// with the same step happen at the same time, so a frontend can show a move
+/// by showing each step in turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoardEvent {
+    /// A card was put on the board from its owner's hand, facing `direction`
+    Placed {
+        card: CardId,
+        position: Position,
+        direction: Direction,
+        step: usize,
+    },
+    Moved {
+        card: CardId,
+        from: Position,
+        to: Position,
+        step: usize,
+    },
+    /// The card was sandwiched between the `by` cards and taken off the board
+    Captured {
+        card: CardId,
+        by: Vec<CardId>,
+        step: usize,
+    },
+    /// A card taken off the board went back to its owner's hand
+    ReturnedToHand {
+        card: CardId,
+        player: Player,
+        step: usize,
+    },
+    ScoreChanged {
+        player: Player,
+        from: usize,
+        to: usize,
+        step: usize,
+    },
+}
+
+impl BoardEvent {
+    pub fn step(&self) -> usize {
+        match *self {
+            BoardEvent::Placed { step, .. }
+            | BoardEvent::Moved { step, .. }
+            | BoardEvent::Captured { step, .. }
+            | BoardEvent::ReturnedToHand { step, .. }
+            | BoardEvent::ScoreChanged { step, .. } => step,
+        }
+    }
+}
+
+/// Everything needed to take back a move, see [`State::undo_move`]. It refers
+/// to cards by index, so it can't be saved.
+#[derive(Debug, Clone)]
+pub struct UndoToken {
+    /// The placed card, the hand slot it came from and its type
+    placed: Option<(Index, usize, CardType)>,
+    movements: Vec<Movement>,
+    /// Cards taken off the board, in the order they were removed
+    removed: Vec<(Index, PlacedCard)>,
+    /// How many cards were returned to the end of each player's hand
+    returned: [usize; 2],
+    /// Hand slots whose waiting counter went down
+    decremented: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Score {
     scores: [usize; 2],
 }
@@ -668,24 +1600,24 @@ impl Score {
     }
 }
 
-struct HashSet<I>(HashMap<I, ()>);
+pub struct HashSet<I>(HashMap<I, ()>);
 
 impl<I> HashSet<I> {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self(HashMap::new())
     }
-    fn with_capacity(capacity: usize) -> Self {
+    pub fn with_capacity(capacity: usize) -> Self {
         Self(HashMap::with_capacity(capacity))
     }
-    fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
 
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.0.len()
     }
 
-    fn iter(&self) -> impl Iterator<Item = &I> {
+    pub fn iter(&self) -> impl Iterator<Item = &I> {
         self.0.iter().map(|x| x.0)
     }
 }
@@ -694,15 +1626,15 @@ impl<I> HashSet<I>
 where
     I: Hash + Eq,
 {
-    fn insert(&mut self, item: I) {
+    pub fn insert(&mut self, item: I) {
         self.0.insert(item, ());
     }
 
-    fn contains(&self, value: &I) -> bool {
+    pub fn contains(&self, value: &I) -> bool {
         self.0.get(value).is_some()
     }
 
-    fn union<'a>(&'a self, other: &'a HashSet<I>) -> impl Iterator<Item = &I> + 'a {
+    pub fn union<'a>(&'a self, other: &'a HashSet<I>) -> impl Iterator<Item = &'a I> + 'a {
         if self.len() >= other.len() {
             self.iter().chain(other.difference(self))
         } else {
@@ -710,12 +1642,18 @@ where
         }
     }
 
-    fn difference<'a>(&'a self, other: &'a HashSet<I>) -> impl Iterator<Item = &I> + 'a {
+    pub fn difference<'a>(&'a self, other: &'a HashSet<I>) -> impl Iterator<Item = &'a I> + 'a {
         self.iter().filter(|x| !other.contains(x))
     }
 }
 
-struct HashSetIter<I>(IterOwned<I, ()>);
+impl<I> Default for HashSet<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct HashSetIter<I>(IterOwned<I, ()>);
 
 impl<I> Iterator for HashSetIter<I> {
     type Item = I;
@@ -748,4 +1686,4 @@ impl<I: Hash + Eq> FromIterator<I> for HashSet<I> {
     }
 }
 
-type Set<I> = HashSet<I>;
+pub type Set<I> = HashSet<I>;
@@ -0,0 +1,228 @@
+//! A compact textual form for moves.
+//!
+//! Placing uses `P<hand index>@<x>,<y><<direction>`, so `P2@3,-1<W` places the
+//! third card in the hand at (3, -1) pushing west. Pushing and picking refer to
+//! cards by where they are on the board, `push 0,0 N` and `pick 1,0`. The long
+//! form `place 2 3,-1 W` is also accepted when parsing.
+
+use core::{fmt::Display, str::FromStr};
+
+use crate::{
+    Direction, HeldCardIndex, Move, PickCardMove, PlaceCardMove, Position, PushCardMove, State,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The move doesn't start with one of `P`, `place`, `push` or `pick`
+    UnknownMove,
+    InvalidHandIndex,
+    InvalidPosition,
+    InvalidDirection,
+    /// There was more text after a complete move
+    TrailingInput,
+    /// The move ended before all its parts were given
+    MissingInput,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ParseError::UnknownMove => "expected a move starting with P, place, push or pick",
+            ParseError::InvalidHandIndex => "invalid hand index",
+            ParseError::InvalidPosition => "invalid position, expected x,y",
+            ParseError::InvalidDirection => "invalid direction, expected one of N, E, S or W",
+            ParseError::TrailingInput => "unexpected text after the move",
+            ParseError::MissingInput => "the move is incomplete",
+        })
+    }
+}
+
+/// A move where cards on the board are referred to by position rather than by
+/// [`crate::CardId`], so it can be written down and read by people.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveNotation {
+    Place {
+        card: usize,
+        position: Position,
+        direction: Direction,
+    },
+    Push {
+        position: Position,
+        direction: Direction,
+    },
+    Pick {
+        position: Position,
+    },
+}
+
+impl MoveNotation {
+    /// Describes the move in terms of the positions of the cards it refers to.
+    /// Returns `None` if a card the move refers to isn't on the board.
+    pub fn from_move(state: &State, m: &Move) -> Option<Self> {
+        Some(match m {
+            Move::PlaceCard(place) => MoveNotation::Place {
+                card: place.card.0,
+                position: place.coordinate,
+                direction: place.direction,
+            },
+            Move::PushCard(push) => MoveNotation::Push {
+                position: state.card_by_id(push.place)?.1.position,
+                direction: push.direction,
+            },
+            Move::PickCard(pick) => MoveNotation::Pick {
+                position: state.card_by_id(pick.card)?.1.position,
+            },
+        })
+    }
+
+    /// Turns the notation back into a move on the given state. Returns `None` if
+    /// there is no card at a position the move refers to.
+    pub fn resolve(&self, state: &State) -> Option<Move> {
+        Some(match *self {
+            MoveNotation::Place {
+                card,
+                position,
+                direction,
+            } => Move::PlaceCard(PlaceCardMove {
+                direction,
+                coordinate: position,
+                card: HeldCardIndex(card),
+            }),
+            MoveNotation::Push {
+                position,
+                direction,
+            } => Move::PushCard(PushCardMove {
+                place: state.card_at_position(position)?.1.id,
+                direction,
+            }),
+            MoveNotation::Pick { position } => Move::PickCard(PickCardMove {
+                card: state.card_at_position(position)?.1.id,
+            }),
+        })
+    }
+}
+
+impl Display for MoveNotation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MoveNotation::Place {
+                card,
+                position,
+                direction,
+            } => write!(f, "P{card}@{position}<{direction}"),
+            MoveNotation::Push {
+                position,
+                direction,
+            } => write!(f, "push {position} {direction}"),
+            MoveNotation::Pick { position } => write!(f, "pick {position}"),
+        }
+    }
+}
+
+impl FromStr for MoveNotation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Some(rest) = s
+            .strip_prefix(['P', 'p'])
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let (card, rest) = rest.split_once('@').ok_or(ParseError::MissingInput)?;
+            let (position, rest) = rest.split_once('<').ok_or(ParseError::MissingInput)?;
+            let mut words = rest.split_whitespace();
+            let direction = words.next().ok_or(ParseError::MissingInput)?;
+
+            if words.next().is_some() {
+                return Err(ParseError::TrailingInput);
+            }
+
+            return Ok(MoveNotation::Place {
+                card: card.parse().map_err(|_| ParseError::InvalidHandIndex)?,
+                position: position.parse()?,
+                direction: direction.parse()?,
+            });
+        }
+
+        let mut words = s.split_whitespace();
+        let mut next = || words.next().ok_or(ParseError::MissingInput);
+
+        let notation = match next()?.to_ascii_lowercase().as_str() {
+            "place" => MoveNotation::Place {
+                card: next()?.parse().map_err(|_| ParseError::InvalidHandIndex)?,
+                position: next()?.parse()?,
+                direction: next()?.parse()?,
+            },
+            "push" => MoveNotation::Push {
+                position: next()?.parse()?,
+                direction: next()?.parse()?,
+            },
+            "pick" => MoveNotation::Pick {
+                position: next()?.parse()?,
+            },
+            _ => return Err(ParseError::UnknownMove),
+        };
+
+        if words.next().is_some() {
+            return Err(ParseError::TrailingInput);
+        }
+
+        Ok(notation)
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Direction::North => "N",
+            Direction::East => "E",
+            Direction::South => "S",
+            Direction::West => "W",
+        })
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let matches =
+            |short: &str, long: &str| s.eq_ignore_ascii_case(short) || s.eq_ignore_ascii_case(long);
+
+        if matches("N", "north") {
+            Ok(Direction::North)
+        } else if matches("E", "east") {
+            Ok(Direction::East)
+        } else if matches("S", "south") {
+            Ok(Direction::South)
+        } else if matches("W", "west") {
+            Ok(Direction::West)
+        } else {
+            Err(ParseError::InvalidDirection)
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{},{}", self.0.x, self.0.y)
+    }
+}
+
+impl FromStr for Position {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s
+            .trim()
+            .split_once(',')
+            .ok_or(ParseError::InvalidPosition)?;
+        let x: i32 = x.trim().parse().map_err(|_| ParseError::InvalidPosition)?;
+        let y: i32 = y.trim().parse().map_err(|_| ParseError::InvalidPosition)?;
+
+        Ok(Position((x, y).into()))
+    }
+}
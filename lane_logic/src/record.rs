@@ -0,0 +1,400 @@
+//! Recording games so they can be saved and replayed.
+//!
+//! A record is plain text, a header describing how the game started followed by
+//! every move in [`crate::notation`]:
+//!
+//! ```text
+//! hand A: Block Normal Normal Ghost Double
+//! hand B: Block Normal Normal Ghost Double(1)
+//! start: A
+//! seed: 1234
+//! rules: score_to_win=4 cooldown=1 capture=either layout=Score@0,0;Score@1,0
+//! moves:
+//! P1@-1,0<E
+//! push -1,0 E
+//! ```
+//!
+//! A held card that is still waiting has the number of turns left in brackets.
+//! The seed is only there if the game had one. The rules are written as
+//! described in [`crate::rules`], and the default rules are used if the line is
+//! missing.
+
+use core::{fmt::Display, str::FromStr};
+
+use alloc::vec::Vec;
+
+use crate::{
+    card::CardType,
+    notation::{self, MoveNotation},
+    rules::{ParseRulesError, RuleSet},
+    HeldCard, Move, MoveError, MoveResult, Player, State,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameRecord {
+    pub player_a: Vec<HeldCard>,
+    pub player_b: Vec<HeldCard>,
+    pub starting_player: Player,
+    /// What anything random in the game, such as the AI, was seeded with. See
+    /// [`crate::rng`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub seed: Option<u64>,
+    pub rules: RuleSet,
+    pub moves: Vec<MoveNotation>,
+}
+
+impl GameRecord {
+    /// Starts a record with the same arguments given to [`State::new`].
+    pub fn new(
+        player_a: Vec<HeldCard>,
+        player_b: Vec<HeldCard>,
+        starting_player: Player,
+        rules: RuleSet,
+    ) -> Self {
+        Self {
+            player_a,
+            player_b,
+            starting_player,
+            seed: None,
+            rules,
+            moves: Vec::new(),
+        }
+    }
+
+    pub fn initial_state(&self) -> State {
+        State::new(
+            self.player_a.clone(),
+            self.player_b.clone(),
+            self.starting_player,
+            self.rules.clone(),
+        )
+    }
+
+    /// Adds a move to the record. This needs the state from *before* the move
+    /// is executed, as that is where the cards the move refers to are. Returns
+    /// `None` if the move refers to a card that isn't on the board.
+    pub fn record_move(&mut self, state: &State, m: &Move) -> Option<()> {
+        self.moves.push(MoveNotation::from_move(state, m)?);
+        Some(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseRecordError {
+    MissingHand(Player),
+    InvalidCard {
+        line: usize,
+    },
+    MissingStartingPlayer,
+    InvalidPlayer {
+        line: usize,
+    },
+    InvalidSeed {
+        line: usize,
+    },
+    InvalidRules {
+        line: usize,
+        error: ParseRulesError,
+    },
+    MissingMoves,
+    /// A line before `moves:` that isn't part of the header
+    UnknownHeader {
+        line: usize,
+    },
+    InvalidMove {
+        line: usize,
+        error: notation::ParseError,
+    },
+}
+
+impl Display for ParseRecordError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseRecordError::MissingHand(player) => {
+                write!(f, "missing hand for player {player:?}")
+            }
+            ParseRecordError::InvalidCard { line } => write!(f, "line {line}: invalid card"),
+            ParseRecordError::MissingStartingPlayer => write!(f, "missing starting player"),
+            ParseRecordError::InvalidPlayer { line } => write!(f, "line {line}: invalid player"),
+            ParseRecordError::InvalidSeed { line } => write!(f, "line {line}: invalid seed"),
+            ParseRecordError::InvalidRules { line, error } => write!(f, "line {line}: {error}"),
+            ParseRecordError::MissingMoves => write!(f, "missing move list"),
+            ParseRecordError::UnknownHeader { line } => write!(f, "line {line}: unknown header"),
+            ParseRecordError::InvalidMove { line, error } => write!(f, "line {line}: {error}"),
+        }
+    }
+}
+
+pub(crate) fn player_name(player: Player) -> &'static str {
+    match player {
+        Player::A => "A",
+        Player::B => "B",
+    }
+}
+
+pub(crate) fn parse_player(player: &str) -> Option<Player> {
+    match player.trim() {
+        "A" => Some(Player::A),
+        "B" => Some(Player::B),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_held_card(card: &str) -> Option<HeldCard> {
+    match card.split_once('(') {
+        Some((card, turns)) => Some(HeldCard::Waiting {
+            card: CardType::from_name(card)?,
+            turns_until_usable: turns
+                .strip_suffix(')')?
+                .parse()
+                .ok()
+                .filter(|&turns| turns > 0)?,
+        }),
+        None => Some(HeldCard::Available(CardType::from_name(card)?)),
+    }
+}
+
+/// Writes a `hand A: ...` line
+pub(crate) fn write_hand(
+    f: &mut core::fmt::Formatter<'_>,
+    player: Player,
+    hand: &[HeldCard],
+) -> core::fmt::Result {
+    write!(f, "hand {}:", player_name(player))?;
+    for card in hand {
+        match card {
+            HeldCard::Available(card) => write!(f, " {}", card.name())?,
+            HeldCard::Waiting {
+                card,
+                turns_until_usable,
+            } => write!(f, " {}({turns_until_usable})", card.name())?,
+        }
+    }
+    writeln!(f)
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (player, hand) in [(Player::A, &self.player_a), (Player::B, &self.player_b)] {
+            write_hand(f, player, hand)?;
+        }
+
+        writeln!(f, "start: {}", player_name(self.starting_player))?;
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed: {seed}")?;
+        }
+        writeln!(f, "rules: {}", self.rules)?;
+        writeln!(f, "moves:")?;
+
+        for m in &self.moves {
+            writeln!(f, "{m}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = ParseRecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut hands = [None, None];
+        let mut starting_player = None;
+        let mut seed = None;
+        let mut rules = None;
+        let mut moves: Option<Vec<MoveNotation>> = None;
+
+        for (line_number, line) in s.lines().enumerate() {
+            let line_number = line_number + 1;
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(moves) = &mut moves {
+                let m = line
+                    .parse()
+                    .map_err(|error| ParseRecordError::InvalidMove {
+                        line: line_number,
+                        error,
+                    })?;
+                moves.push(m);
+            } else if let Some(hand) = line.strip_prefix("hand ") {
+                let (player, cards) = hand
+                    .split_once(':')
+                    .ok_or(ParseRecordError::InvalidPlayer { line: line_number })?;
+                let player = parse_player(player)
+                    .ok_or(ParseRecordError::InvalidPlayer { line: line_number })?;
+
+                hands[player as usize] = Some(
+                    cards
+                        .split_whitespace()
+                        .map(|card| {
+                            parse_held_card(card)
+                                .ok_or(ParseRecordError::InvalidCard { line: line_number })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                );
+            } else if let Some(player) = line.strip_prefix("start:") {
+                starting_player = Some(
+                    parse_player(player)
+                        .ok_or(ParseRecordError::InvalidPlayer { line: line_number })?,
+                );
+            } else if let Some(value) = line.strip_prefix("seed:") {
+                seed = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| ParseRecordError::InvalidSeed { line: line_number })?,
+                );
+            } else if let Some(rule_set) = line.strip_prefix("rules:") {
+                rules = Some(
+                    rule_set
+                        .parse()
+                        .map_err(|error| ParseRecordError::InvalidRules {
+                            line: line_number,
+                            error,
+                        })?,
+                );
+            } else if line == "moves:" {
+                moves = Some(Vec::new());
+            } else {
+                return Err(ParseRecordError::UnknownHeader { line: line_number });
+            }
+        }
+
+        let [player_a, player_b] = hands;
+
+        Ok(GameRecord {
+            player_a: player_a.ok_or(ParseRecordError::MissingHand(Player::A))?,
+            player_b: player_b.ok_or(ParseRecordError::MissingHand(Player::B))?,
+            starting_player: starting_player.ok_or(ParseRecordError::MissingStartingPlayer)?,
+            seed,
+            rules: rules.unwrap_or_default(),
+            moves: moves.ok_or(ParseRecordError::MissingMoves)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// A push or pick in the record refers to a position with no card on it
+    NoCardAt { move_number: usize },
+    /// The recorded move can't be made in the position it was recorded in
+    IllegalMove {
+        move_number: usize,
+        error: MoveError,
+    },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReplayError::NoCardAt { move_number } => {
+                write!(f, "move {move_number}: there is no card at that position")
+            }
+            ReplayError::IllegalMove { move_number, error } => {
+                write!(f, "move {move_number}: {error}")
+            }
+        }
+    }
+}
+
+/// Steps through a recorded game one move at a time, in either direction.
+pub struct Replay {
+    record: GameRecord,
+    /// `states[n]` is the state after `n` moves have been played
+    states: Vec<State>,
+    /// `results[n]` is the result of playing move `n`
+    results: Vec<MoveResult>,
+    current: usize,
+}
+
+impl Replay {
+    pub fn new(record: GameRecord) -> Self {
+        let initial = record.initial_state();
+        Self {
+            record,
+            states: alloc::vec![initial],
+            results: Vec::new(),
+            current: 0,
+        }
+    }
+
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    pub fn state(&self) -> &State {
+        &self.states[self.current]
+    }
+
+    /// The number of moves that have been played to reach the current state.
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    pub fn len(&self) -> usize {
+        self.record.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.record.moves.is_empty()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current == self.len()
+    }
+
+    /// The result of the move that led to the current state.
+    pub fn last_result(&self) -> Option<&MoveResult> {
+        self.current.checked_sub(1).map(|n| &self.results[n])
+    }
+
+    /// Plays the next move, returning its result. Returns `Ok(None)` at the end
+    /// of the game.
+    pub fn step_forward(&mut self) -> Result<Option<&MoveResult>, ReplayError> {
+        if self.is_finished() {
+            return Ok(None);
+        }
+
+        if self.current + 1 == self.states.len() {
+            let move_number = self.current;
+            let mut state = self.states[self.current].clone();
+            let m = self.record.moves[move_number]
+                .resolve(&state)
+                .ok_or(ReplayError::NoCardAt { move_number })?;
+            let result = state
+                .try_execute_move(&m)
+                .map_err(|error| ReplayError::IllegalMove { move_number, error })?;
+
+            self.states.push(state);
+            self.results.push(result);
+        }
+
+        self.current += 1;
+        Ok(self.last_result())
+    }
+
+    /// Goes back a move, returning the result of the move that was taken back.
+    /// Returns `None` at the start of the game.
+    pub fn step_backward(&mut self) -> Option<&MoveResult> {
+        let move_number = self.current.checked_sub(1)?;
+        self.current = move_number;
+        Some(&self.results[move_number])
+    }
+
+    /// Moves to the state after `position` moves have been played.
+    pub fn seek(&mut self, position: usize) -> Result<(), ReplayError> {
+        let position = position.min(self.len());
+        while self.current > position {
+            self.step_backward();
+        }
+        while self.current < position {
+            self.step_forward()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+//! Seedable random numbers, so that anything random in a game can be repeated
+//! from its seed.
+//!
+//! [`Pcg32`] is the PCG-XSH-RR generator from <https://www.pcg-random.org>. It
+//! only uses integer arithmetic, so the same seed gives the same numbers on the
+//! GBA and on a host.
+
+/// A source of random numbers.
+pub trait Rng {
+    fn next_u32(&mut self) -> u32;
+}
+
+impl<R: Rng + ?Sized> Rng for &mut R {
+    fn next_u32(&mut self) -> u32 {
+        (**self).next_u32()
+    }
+}
+
+const MULTIPLIER: u64 = 6364136223846793005;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    /// Generators with the same seed but different streams give unrelated
+    /// numbers, so one seed can be used for several things.
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Pcg32 {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.step();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.step();
+        rng
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+}
+
+impl Rng for Pcg32 {
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+}
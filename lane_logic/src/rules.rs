@@ -0,0 +1,208 @@
+//! The parts of the rules that can be changed to try out variants of the game.
+//!
+//! A rule set can be written on a single line as space separated `key=value`
+//! pairs, which is how it appears in a [`crate::record::GameRecord`]:
+//!
+//! ```text
+//! score_to_win=4 cooldown=1 cooldown.Ghost=2 capture=either max_turns=200 connected=yes layout=Score@0,0;Score@1,0
+//! ```
+//!
+//! Cards in the layout can be given an owner with `Normal:A@2,0`.
+
+use core::{fmt::Display, str::FromStr};
+
+use alloc::vec::Vec;
+
+use crate::{card::CardType, Player, Position};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SandwichRule {
+    /// A card is captured when it is between opponent cards on either axis
+    EitherAxis,
+    /// A card is only captured when it is surrounded on both axes
+    BothAxes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleSet {
+    /// A player wins when they score at least this much and their opponent doesn't
+    pub score_to_win: usize,
+    /// How many turns a card returned to the hand waits before it can be played
+    pub default_cooldown: usize,
+    /// Cooldowns for card types which don't use the default
+    pub cooldowns: Vec<(CardType, usize)>,
+    /// The cards on the board at the start of the game
+    pub starting_layout: Vec<(Position, CardType, Option<Player>)>,
+    pub capture: SandwichRule,
+    /// The game is a draw after this many turns if nobody has won
+    pub max_turns: Option<usize>,
+    /// Moves which would split the cards into more than one group aren't allowed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub connected: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            score_to_win: 4,
+            default_cooldown: 1,
+            cooldowns: Vec::new(),
+            starting_layout: alloc::vec![
+                (Position((0, 0).into()), CardType::Score, None),
+                (Position((1, 0).into()), CardType::Score, None),
+            ],
+            capture: SandwichRule::EitherAxis,
+            max_turns: None,
+            connected: false,
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn cooldown(&self, card: CardType) -> usize {
+        self.cooldowns
+            .iter()
+            .find(|(c, _)| *c == card)
+            .map_or(self.default_cooldown, |&(_, cooldown)| cooldown)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseRulesError {
+    UnknownRule,
+    InvalidNumber,
+    InvalidCard,
+    InvalidCapture,
+    InvalidConnected,
+    InvalidLayout,
+}
+
+impl Display for ParseRulesError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ParseRulesError::UnknownRule => "unknown rule",
+            ParseRulesError::InvalidNumber => "invalid number",
+            ParseRulesError::InvalidCard => "invalid card",
+            ParseRulesError::InvalidCapture => "invalid capture rule, expected either or both",
+            ParseRulesError::InvalidConnected => "invalid connected rule, expected yes or no",
+            ParseRulesError::InvalidLayout => "invalid layout, expected card@x,y separated by ;",
+        })
+    }
+}
+
+impl Display for RuleSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "score_to_win={} cooldown={}",
+            self.score_to_win, self.default_cooldown
+        )?;
+
+        for (card, cooldown) in &self.cooldowns {
+            write!(f, " cooldown.{}={cooldown}", card.name())?;
+        }
+
+        let capture = match self.capture {
+            SandwichRule::EitherAxis => "either",
+            SandwichRule::BothAxes => "both",
+        };
+        write!(f, " capture={capture}")?;
+
+        if let Some(max_turns) = self.max_turns {
+            write!(f, " max_turns={max_turns}")?;
+        }
+
+        if self.connected {
+            write!(f, " connected=yes")?;
+        }
+
+        write!(f, " layout=")?;
+
+        for (i, (position, card, owner)) in self.starting_layout.iter().enumerate() {
+            if i != 0 {
+                write!(f, ";")?;
+            }
+            write!(f, "{}", card.name())?;
+            match owner {
+                Some(Player::A) => write!(f, ":A")?,
+                Some(Player::B) => write!(f, ":B")?,
+                None => {}
+            }
+            write!(f, "@{position}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_layout_card(card: &str) -> Result<(Position, CardType, Option<Player>), ParseRulesError> {
+    let (card, position) = card.split_once('@').ok_or(ParseRulesError::InvalidLayout)?;
+    let (card, owner) = match card.split_once(':') {
+        Some((card, "A")) => (card, Some(Player::A)),
+        Some((card, "B")) => (card, Some(Player::B)),
+        Some(_) => return Err(ParseRulesError::InvalidLayout),
+        None => (card, None),
+    };
+
+    Ok((
+        position
+            .parse()
+            .map_err(|_| ParseRulesError::InvalidLayout)?,
+        CardType::from_name(card).ok_or(ParseRulesError::InvalidCard)?,
+        owner,
+    ))
+}
+
+impl FromStr for RuleSet {
+    type Err = ParseRulesError;
+
+    /// Parses rules written as `key=value` pairs. Any rule that isn't given
+    /// keeps its default.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = RuleSet::default();
+
+        for rule in s.split_whitespace() {
+            let (key, value) = rule.split_once('=').ok_or(ParseRulesError::UnknownRule)?;
+            let number = || value.parse().map_err(|_| ParseRulesError::InvalidNumber);
+
+            match key {
+                "score_to_win" => rules.score_to_win = number()?,
+                "cooldown" => rules.default_cooldown = number()?,
+                "max_turns" => rules.max_turns = Some(number()?),
+                "capture" => {
+                    rules.capture = match value {
+                        "either" => SandwichRule::EitherAxis,
+                        "both" => SandwichRule::BothAxes,
+                        _ => return Err(ParseRulesError::InvalidCapture),
+                    }
+                }
+                "connected" => {
+                    rules.connected = match value {
+                        "yes" => true,
+                        "no" => false,
+                        _ => return Err(ParseRulesError::InvalidConnected),
+                    }
+                }
+                "layout" => {
+                    rules.starting_layout = value
+                        .split(';')
+                        .filter(|card| !card.is_empty())
+                        .map(parse_layout_card)
+                        .collect::<Result<_, _>>()?;
+                }
+                _ => {
+                    let card = key
+                        .strip_prefix("cooldown.")
+                        .ok_or(ParseRulesError::UnknownRule)?;
+                    let card = CardType::from_name(card).ok_or(ParseRulesError::InvalidCard)?;
+                    rules.cooldowns.retain(|(c, _)| *c != card);
+                    rules.cooldowns.push((card, number()?));
+                }
+            }
+        }
+
+        Ok(rules)
+    }
+}
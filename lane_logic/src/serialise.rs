@@ -0,0 +1,162 @@
+//! Serde support for the types which can't just derive it.
+//!
+//! Card types are saved by name, and a [`State`] is saved as its hands and
+//! the cards on the board in order of [`CardId`]. Indices aren't saved, a
+//! loaded state gives its cards new ones. Moves and move results refer to
+//! cards by id, so they still refer to the right cards once loaded.
+
+use alloc::{string::String, vec::Vec};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    card::{CardData, CardType},
+    rules::RuleSet,
+    Board, CardId, Hand, HeldCard, PlacedCard, Player, Position, State,
+};
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.0.x, self.0.y).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (x, y) = <(i32, i32)>::deserialize(deserializer)?;
+        Ok(Position((x, y).into()))
+    }
+}
+
+impl Serialize for CardType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for CardType {
+    /// Only built in cards can be loaded, as there is no way to find the
+    /// behaviour of a custom card from its name.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        CardType::from_name(&name)
+            .ok_or_else(|| D::Error::custom(format_args!("unknown card type {name}")))
+    }
+}
+
+impl Serialize for CardData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_type().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CardData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(CardType::deserialize(deserializer)?.to_data())
+    }
+}
+
+/// A held card as it is saved, before checking that a waiting card has turns
+/// left to wait
+#[derive(Deserialize)]
+#[serde(rename = "HeldCard")]
+enum HeldCardData {
+    Available(CardType),
+    Waiting {
+        card: CardType,
+        turns_until_usable: usize,
+    },
+}
+
+impl<'de> Deserialize<'de> for HeldCard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match HeldCardData::deserialize(deserializer)? {
+            HeldCardData::Available(card) => Ok(HeldCard::Available(card)),
+            HeldCardData::Waiting {
+                turns_until_usable: 0,
+                ..
+            } => Err(D::Error::custom("a waiting card has no turns left to wait")),
+            HeldCardData::Waiting {
+                card,
+                turns_until_usable,
+            } => Ok(HeldCard::Waiting {
+                card,
+                turns_until_usable,
+            }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StateRef<'a> {
+    turn: Player,
+    hands: [&'a [HeldCard]; 2],
+    cards: Vec<&'a PlacedCard>,
+    next_card_id: CardId,
+    rules: &'a RuleSet,
+    history: &'a [u64],
+    turns_before_history: usize,
+}
+
+#[derive(Deserialize)]
+struct StateData {
+    turn: Player,
+    hands: [Vec<HeldCard>; 2],
+    cards: Vec<PlacedCard>,
+    next_card_id: CardId,
+    rules: RuleSet,
+    history: Vec<u64>,
+    #[serde(default)]
+    turns_before_history: usize,
+}
+
+impl Serialize for State {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut cards: Vec<_> = self.board_state().map(|(_, card)| card).collect();
+        cards.sort_by_key(|card| card.id);
+
+        StateRef {
+            turn: self.turn,
+            hands: [self.player_hand(Player::A), self.player_hand(Player::B)],
+            cards,
+            next_card_id: self.board.next_id,
+            rules: &self.rules,
+            history: &self.history,
+            turns_before_history: self.turns_before_history,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let StateData {
+            turn,
+            hands: [player_a, player_b],
+            cards,
+            next_card_id,
+            rules,
+            history,
+            turns_before_history,
+        } = StateData::deserialize(deserializer)?;
+
+        let board = Board::with_cards(cards, next_card_id).ok_or_else(|| {
+            D::Error::custom("two cards have the same id or position, or an id is too big")
+        })?;
+
+        let mut state = State {
+            turn,
+            board,
+            hands: [Hand::new(player_a), Hand::new(player_b)],
+            hash: 0,
+            rules,
+            history,
+            turns_before_history,
+        };
+        state.update_hash();
+        if state.history.is_empty() {
+            state.history.push(state.hash);
+        }
+
+        Ok(state)
+    }
+}
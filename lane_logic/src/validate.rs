@@ -0,0 +1,135 @@
+//! Checks that a state hasn't been corrupted, see [`State::validate`].
+//!
+//! With the `validate` feature every move made with [`State::execute_move`],
+//! [`State::try_execute_move`] or taken back with [`State::undo_move`] is
+//! followed by a check, which panics with the report if anything is wrong.
+
+use core::fmt::Display;
+
+use agb_hashmap::HashMap;
+use alloc::vec::Vec;
+
+use crate::{card::CardType, CardId, HeldCard, Player, Position, State};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Two cards are in the same place
+    Overlapping {
+        position: Position,
+        cards: [CardId; 2],
+    },
+    /// Looking the card up by its position or id finds something else
+    LostCard(CardId),
+    /// Score cards are placed by the game and belong to nobody
+    OwnedScoreCard { card: CardId, owner: Player },
+    /// A waiting card should have become available already
+    NotWaiting { player: Player, slot: usize },
+    /// The rules need the cards to stay in one group, but they are split up
+    Disconnected,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValidationError::Overlapping {
+                position,
+                cards: [a, b],
+            } => write!(f, "cards {} and {} are both at {position}", a.0, b.0),
+            ValidationError::LostCard(card) => {
+                write!(f, "card {} can't be found by its position or id", card.0)
+            }
+            ValidationError::OwnedScoreCard { card, owner } => {
+                write!(f, "score card {} belongs to player {owner:?}", card.0)
+            }
+            ValidationError::NotWaiting { player, slot } => write!(
+                f,
+                "card {slot} in player {player:?}'s hand is waiting for no turns"
+            ),
+            ValidationError::Disconnected => write!(f, "the cards are not all connected"),
+        }
+    }
+}
+
+/// Everything [`State::validate`] found wrong with a state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the state is invalid:")?;
+        for error in &self.errors {
+            write!(f, "\n  {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl State {
+    /// Checks the things which should always be true of a state, which a bug
+    /// in a card's behaviour could break.
+    pub fn validate(&self) -> Result<(), ValidationReport> {
+        let mut errors = Vec::new();
+        let mut by_position: HashMap<Position, CardId> = HashMap::new();
+
+        for (idx, card) in self.board_state() {
+            if let Some(&other) = by_position.get(&card.position) {
+                errors.push(ValidationError::Overlapping {
+                    position: card.position,
+                    cards: [other, card.id],
+                });
+            } else {
+                by_position.insert(card.position, card.id);
+            }
+
+            // with overlapping cards only one can be found by position, but it
+            // should at least be one of the cards there
+            let found = self
+                .board
+                .get_card_position(card.position)
+                .and_then(|found| self.board.get_card(found));
+            if self.board.by_id.get(&card.id) != Some(&idx)
+                || found.is_none_or(|found| found.position != card.position)
+            {
+                errors.push(ValidationError::LostCard(card.id));
+            }
+
+            if let (CardType::Score, Some(owner)) = (card.card.to_type(), card.belonging_player) {
+                errors.push(ValidationError::OwnedScoreCard {
+                    card: card.id,
+                    owner,
+                });
+            }
+        }
+
+        for player in [Player::A, Player::B] {
+            for (slot, card) in self.player_hand(player).iter().enumerate() {
+                if let HeldCard::Waiting {
+                    turns_until_usable: 0,
+                    ..
+                } = card
+                {
+                    errors.push(ValidationError::NotWaiting { player, slot });
+                }
+            }
+        }
+
+        if self.rules.connected && !self.board.is_connected() {
+            errors.push(ValidationError::Disconnected);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationReport { errors })
+        }
+    }
+
+    #[cfg(feature = "validate")]
+    pub(crate) fn validate_or_panic(&self, after: &dyn core::fmt::Debug) {
+        if let Err(report) = self.validate() {
+            panic!("{report}\nafter {after:?}\n{self:?}");
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Keys for Zobrist hashing of states.
+//!
+//! The board is unbounded so rather than a table of random numbers, each key is
+//! made by mixing the thing being hashed through the splitmix64 finaliser.
+
+use agb_fixnum::Vector2D;
+
+use crate::{card::CardType, HeldCard, Player};
+
+const BOARD: u64 = 1;
+const HAND: u64 = 2;
+
+/// Included in the hash when it is player B's turn.
+pub(crate) const PLAYER_B_TO_MOVE: u64 = mix(3 << 56);
+
+const fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Card types are told apart by name so that custom cards get their own keys.
+fn card_bits(card: CardType) -> u64 {
+    card.name().bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn owner_bits(owner: Option<Player>) -> u64 {
+    match owner {
+        None => 0,
+        Some(Player::A) => 1,
+        Some(Player::B) => 2,
+    }
+}
+
+/// The key for a card on the board, where `position` is relative to the top
+/// left of the board so that the hash doesn't depend on where the board is.
+pub(crate) fn card_key(position: Vector2D<i32>, card: CardType, owner: Option<Player>) -> u64 {
+    mix(card_bits(card)
+        ^ mix(u64::from(position.x as u16)
+            | u64::from(position.y as u16) << 16
+            | owner_bits(owner) << 40
+            | BOARD << 56))
+}
+
+/// The key for a card in a hand. Hands are hashed by adding these keys rather
+/// than xoring them, so that two of the same card don't cancel out.
+pub(crate) fn held_card_key(player: Player, card: &HeldCard) -> u64 {
+    let (card, turns_until_usable) = match *card {
+        HeldCard::Available(card) => (card, 0),
+        HeldCard::Waiting {
+            card,
+            turns_until_usable,
+        } => (card, turns_until_usable as u64),
+    };
+
+    mix(card_bits(card)
+        ^ mix(turns_until_usable & 0xffff_ffff | owner_bits(Some(player)) << 40 | HAND << 56))
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use common::{hand, play_random_games, RandomMoves};
+use std::collections::BTreeSet;
+
+use lane_logic::{
+    encoding::{decode, encode},
+    rules::RuleSet,
+    BoardEvent, CardId, Player, State,
+};
+
+#[test]
+fn placed_cards_get_new_ids() {
+    let mut next_id = 0;
+
+    play_random_games(
+        0x1d5,
+        20,
+        40,
+        || State::new(hand(), hand(), Player::A, RuleSet::default()),
+        |state, m| {
+            // only the starting layout has been put down at the start of a game
+            if state.turns_played() == 0 {
+                next_id = state.board_state().count() as u32;
+            }
+
+            let before = state.clone();
+            let result = state.execute_move(m);
+
+            for (id, _, card) in &result.placed {
+                assert_eq!(*id, CardId(next_id));
+                assert_eq!(card.id, *id);
+                next_id += 1;
+            }
+
+            // cards which stay on the board keep their id
+            for (idx, card) in before.board_state() {
+                if let Some(after) = state.card(idx) {
+                    assert_eq!(after.id, card.id);
+                    assert_eq!(state.card_by_id(card.id).unwrap().0, idx);
+                }
+            }
+
+            for (id, card) in &result.removed {
+                assert_eq!(card.id, *id);
+                assert!(state.card_by_id(*id).is_none());
+            }
+
+            result
+        },
+    );
+}
+
+#[test]
+fn undoing_a_placement_hands_out_its_id_again() {
+    let mut state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    let m = &state.enumerate_possible_moves()[0];
+
+    let (result, undo) = state.execute_move_with_undo(m);
+    let id = result.placed[0].2.id;
+    state.undo_move(undo);
+
+    assert!(state.card_by_id(id).is_none());
+    assert_eq!(state.execute_move(m).placed[0].2.id, id);
+}
+
+#[test]
+fn moves_mean_the_same_in_a_decoded_copy() {
+    let mut rng = RandomMoves::new(0x1d6);
+    let mut state = State::new(hand(), hand(), Player::A, RuleSet::default());
+
+    for _ in 0..30 {
+        let moves = state.enumerate_possible_moves();
+        if moves.is_empty() {
+            break;
+        }
+
+        // the decoded copy puts its cards down in order of position, so they
+        // have other indices
+        let mut decoded = decode(&encode(&state).unwrap()).unwrap();
+        let m = rng.choose(&moves);
+        assert!(decoded.enumerate_possible_moves().contains(m));
+
+        let result = state.execute_move(m);
+        let decoded_result = decoded.execute_move(m);
+        assert_eq!(decoded_result.events, result.events);
+        assert_eq!(decoded.hash(), state.hash());
+
+        if result.outcome.is_over() {
+            break;
+        }
+    }
+}
+
+#[test]
+fn results_name_the_same_cards_as_their_events() {
+    play_random_games(
+        0x1d7,
+        20,
+        40,
+        || State::new(hand(), hand(), Player::A, RuleSet::default()),
+        |state, m| {
+            let result = state.execute_move(m);
+
+            let mut placed = BTreeSet::new();
+            let mut moved = BTreeSet::new();
+            let mut removed = BTreeSet::new();
+            for event in &result.events {
+                match event {
+                    BoardEvent::Placed { card, .. } => placed.insert(*card),
+                    BoardEvent::Moved { card, .. } => moved.insert(*card),
+                    BoardEvent::Captured { card, .. } | BoardEvent::ReturnedToHand { card, .. } => {
+                        removed.insert(*card)
+                    }
+                    BoardEvent::ScoreChanged { .. } => false,
+                };
+            }
+
+            assert_eq!(
+                result
+                    .placed
+                    .iter()
+                    .map(|(id, ..)| *id)
+                    .collect::<BTreeSet<_>>(),
+                placed
+            );
+            assert_eq!(
+                result
+                    .moved
+                    .iter()
+                    .map(|(id, _)| *id)
+                    .collect::<BTreeSet<_>>(),
+                moved
+            );
+            assert_eq!(
+                result
+                    .removed
+                    .iter()
+                    .map(|(id, _)| *id)
+                    .collect::<BTreeSet<_>>(),
+                removed
+            );
+
+            // moved cards which are still on the board are where the result says
+            for (id, card) in &result.moved {
+                if let Some((_, now)) = state.card_by_id(*id) {
+                    assert_eq!(now.position, card.position);
+                }
+            }
+
+            result
+        },
+    );
+}
@@ -0,0 +1,74 @@
+//! Things shared by the tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use lane_logic::{
+    card::CardType,
+    rng::{Pcg32, Rng},
+    CardId, HeldCard, Move, MoveResult, Position, State,
+};
+
+pub mod strategies;
+
+/// The position at `(x, y)`
+pub fn position(x: i32, y: i32) -> Position {
+    Position((x, y).into())
+}
+
+/// The id of the card at `(x, y)`, which must be there
+pub fn id_at(state: &State, x: i32, y: i32) -> CardId {
+    state.card_at_position(position(x, y)).unwrap().1.id
+}
+
+/// One of every card a player can place, and a spare normal card
+pub fn hand() -> Vec<HeldCard> {
+    [
+        CardType::Block,
+        CardType::Normal,
+        CardType::Normal,
+        CardType::Ghost,
+        CardType::Double,
+        CardType::Redirect,
+        CardType::Reverse,
+    ]
+    .into_iter()
+    .map(HeldCard::Available)
+    .collect()
+}
+
+/// Chooses between moves at random, making the same choices every run.
+pub struct RandomMoves(Pcg32);
+
+impl RandomMoves {
+    pub fn new(seed: u64) -> Self {
+        Self(Pcg32::new(seed, 0))
+    }
+
+    pub fn choose<'a>(&mut self, moves: &'a [Move]) -> &'a Move {
+        &moves[self.0.next_u32() as usize % moves.len()]
+    }
+}
+
+/// Plays `games` games of random moves, each starting from `start()` and
+/// lasting at most `turns` moves. `play` is given the state and the chosen
+/// move, and makes the move. A game stops early once nobody can move or it is
+/// over.
+pub fn play_random_games(
+    seed: u64,
+    games: usize,
+    turns: usize,
+    start: impl Fn() -> State,
+    mut play: impl FnMut(&mut State, &Move) -> MoveResult,
+) {
+    let mut rng = RandomMoves::new(seed);
+
+    for _ in 0..games {
+        let mut state = start();
+
+        for _ in 0..turns {
+            let moves = state.enumerate_possible_moves();
+            if moves.is_empty() || play(&mut state, rng.choose(&moves)).outcome.is_over() {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Proptest strategies for starting hands and layouts.
+
+use std::collections::HashSet;
+
+use lane_logic::{card::CardType, rules::RuleSet, Direction, HeldCard, Player, Position};
+use proptest::prelude::*;
+
+use super::position;
+
+/// Ghosts, redirects and doubles interact in the most surprising ways, so they
+/// come up more often
+pub fn card_type() -> impl Strategy<Value = CardType> {
+    prop_oneof![
+        1 => Just(CardType::Block),
+        2 => Just(CardType::Normal),
+        3 => Just(CardType::Double),
+        3 => Just(CardType::Ghost),
+        3 => Just(CardType::Redirect),
+        1 => Just(CardType::Reverse),
+    ]
+}
+
+pub fn hand() -> impl Strategy<Value = Vec<HeldCard>> {
+    prop::collection::vec(card_type().prop_map(HeldCard::Available), 1..8)
+}
+
+pub fn owner() -> impl Strategy<Value = Option<Player>> {
+    prop_oneof![Just(None), Just(Some(Player::A)), Just(Some(Player::B))]
+}
+
+pub fn direction() -> impl Strategy<Value = Direction> {
+    prop_oneof![
+        Just(Direction::North),
+        Just(Direction::East),
+        Just(Direction::South),
+        Just(Direction::West),
+    ]
+}
+
+/// Up to `max_cards` extra cards around the score cards, leaving out any which
+/// would share a position with a card before them
+pub fn layout(
+    max_cards: usize,
+) -> impl Strategy<Value = Vec<(Position, CardType, Option<Player>)>> {
+    prop::collection::vec(
+        ((-3..4, -3..4), card_type(), owner())
+            .prop_map(|((x, y), card, owner)| (position(x, y), card, owner)),
+        0..max_cards,
+    )
+    .prop_map(|mut layout| {
+        let mut taken: HashSet<_> = RuleSet::default()
+            .starting_layout
+            .iter()
+            .map(|&(position, _, _)| position)
+            .collect();
+        layout.retain(|&(position, _, _)| taken.insert(position));
+        layout
+    })
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use common::position;
+use lane_logic::{
+    card::{
+        normal::{normal_placement, normal_placement_rule},
+        Card, CardData, CardType, CustomCardType, PushChain,
+    },
+    rules::RuleSet,
+    Board, Direction, HeldCard, Index, Move, MoveError, PlaceStatus, Player, Position,
+    PushCardMove, Set, State,
+};
+
+/// A card which can never be moved once it is on the board
+#[derive(Debug, Clone, Default)]
+struct Anchor {}
+
+const ANCHOR: CustomCardType = CustomCardType::new::<Anchor>("Anchor");
+
+impl Card for Anchor {
+    fn push(
+        _board: &mut Board,
+        _self_index: Index,
+        _direction: Direction,
+        _chain: &PushChain,
+    ) -> Set<Index> {
+        Set::new()
+    }
+
+    fn can_place(
+        board: &Board,
+        _player: Player,
+        position: Position,
+        direction: Direction,
+    ) -> PlaceStatus {
+        normal_placement_rule(board, position, direction)
+    }
+
+    fn place(
+        board: &mut Board,
+        player: Player,
+        position: Position,
+        direction: Direction,
+    ) -> (Index, Set<Index>) {
+        normal_placement(board, player, position, direction, CardData::Custom(ANCHOR))
+    }
+}
+
+fn rules_with(layout: &[(Position, CardType, Option<Player>)]) -> RuleSet {
+    let mut rules = RuleSet::default();
+    rules.starting_layout.extend_from_slice(layout);
+    rules
+}
+
+fn hand() -> Vec<HeldCard> {
+    [CardType::Custom(ANCHOR), CardType::Normal, CardType::Normal]
+        .into_iter()
+        .map(HeldCard::Available)
+        .collect()
+}
+
+#[test]
+fn custom_cards_can_be_placed() {
+    let mut state = State::new(hand(), hand(), Player::A, RuleSet::default());
+
+    let place = state
+        .enumerate_possible_moves()
+        .into_iter()
+        .find(|m| matches!(m, Move::PlaceCard(place) if place.card.0 == 0))
+        .expect("the custom card should be placeable");
+
+    let result = state.try_execute_move(&place).unwrap();
+    let (id, _, _) = result.placed[0];
+
+    assert_eq!(
+        state.card_by_id(id).unwrap().1.card.to_type(),
+        CardType::Custom(ANCHOR)
+    );
+    assert_eq!(state.player_hand(Player::A).len(), 2);
+}
+
+#[test]
+fn custom_cards_can_block_pushes() {
+    let mut state = State::new(
+        hand(),
+        hand(),
+        Player::A,
+        rules_with(&[
+            (position(2, 0), CardType::Custom(ANCHOR), Some(Player::B)),
+            (position(-1, 0), CardType::Normal, Some(Player::A)),
+        ]),
+    );
+
+    let pusher = state.card_at_position(position(-1, 0)).unwrap().1.id;
+    let anchor = state.card_at_position(position(2, 0)).unwrap().1.id;
+
+    let push = Move::PushCard(PushCardMove {
+        place: pusher,
+        direction: Direction::East,
+    });
+
+    assert_eq!(
+        state.try_execute_move(&push).unwrap_err(),
+        MoveError::PushBlocked {
+            card: pusher,
+            blocked_by: anchor
+        }
+    );
+}
+
+#[test]
+fn custom_cards_can_be_captured() {
+    let state = State::new(
+        hand(),
+        hand(),
+        Player::A,
+        rules_with(&[
+            (position(0, 1), CardType::Custom(ANCHOR), Some(Player::B)),
+            (position(-1, 1), CardType::Normal, Some(Player::A)),
+        ]),
+    );
+
+    let anchor = state.card_at_position(position(0, 1)).unwrap().1.id;
+
+    let captured = state.enumerate_possible_moves().into_iter().any(|m| {
+        let mut state = state.clone();
+        let result = state.execute_move(&m);
+        result.removed.iter().any(|&(id, _)| id == anchor)
+    });
+
+    assert!(captured, "no move captured the custom card");
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use common::{hand, play_random_games};
+use lane_logic::{
+    card::CardType, diagram::ParseDiagramError, notation::MoveNotation, rules::RuleSet, HeldCard,
+    Player, State,
+};
+
+/// Plays the move on the drawn state, returning the drawing of the board after
+fn play(drawing: &str, m: &str) -> String {
+    let mut state: State = drawing.parse().unwrap();
+    let m = m.parse::<MoveNotation>().unwrap().resolve(&state).unwrap();
+    state.try_execute_move(&m).unwrap();
+    state.board().to_string()
+}
+
+/// Draws just the board again, in the same way it is drawn after a move
+fn board(drawing: &str) -> String {
+    let state: State = format!("{drawing}\nturn: A\nhand A:\nhand B:")
+        .parse()
+        .unwrap();
+    state.board().to_string()
+}
+
+#[test]
+fn states_are_drawn_as_a_grid() {
+    let mut state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    let m = "P1@-1,0<E".parse::<MoveNotation>().unwrap();
+    state.execute_move(&m.resolve(&state).unwrap());
+
+    assert_eq!(
+        state.to_string(),
+        "       0   1   2
+   0   N (S) (S)
+turn: B
+hand A: Block Normal Ghost Double Redirect Reverse
+hand B: Block Normal Normal Ghost Double Redirect Reverse
+"
+    );
+}
+
+#[test]
+fn drawings_round_trip() {
+    let rules = RuleSet {
+        max_turns: Some(40),
+        ..RuleSet::default()
+    };
+
+    play_random_games(
+        0xd1a9,
+        20,
+        40,
+        || State::new(hand(), hand(), Player::B, rules.clone()),
+        |state, m| {
+            let drawing = state.to_string();
+            let loaded: State = drawing.parse().unwrap();
+
+            assert_eq!(loaded.to_string(), drawing);
+            assert_eq!(loaded.hash(), state.hash());
+            assert_eq!(loaded.rules(), state.rules());
+            assert_eq!(
+                loaded.enumerate_possible_moves().len(),
+                state.enumerate_possible_moves().len()
+            );
+
+            state.execute_move(m)
+        },
+    );
+}
+
+#[test]
+fn ghosts_pass_through_cards() {
+    assert_eq!(
+        play(
+            "
+               -1  0  1
+             0  G (S)(S)
+            turn: A
+            hand A:
+            hand B: Normal
+            ",
+            "push -1,0 E",
+        ),
+        board(
+            "
+               0  1  2
+             0 (S)(S) G
+            "
+        ),
+    );
+}
+
+#[test]
+fn doubles_push_twice() {
+    assert_eq!(
+        play(
+            "
+               -1  0  1
+             0  D (S)(S)
+            turn: A
+            hand A:
+            hand B: Normal
+            ",
+            "push -1,0 E",
+        ),
+        board(
+            "
+               1  2  3
+             0 D (S)(S)
+            "
+        ),
+    );
+}
+
+#[test]
+fn redirects_push_to_the_sides() {
+    assert_eq!(
+        play(
+            "
+               -1  0  1
+            -1  n
+             0  R (S)(S)
+             1  n
+            turn: A
+            hand A:
+            hand B: Normal
+            ",
+            "push -1,0 E",
+        ),
+        board(
+            "
+               -1  0  1  2
+            -2  n
+            -1  .
+             0  .  R (S)(S)
+             1  .
+             2  n
+            "
+        ),
+    );
+}
+
+#[test]
+fn bad_drawings_are_rejected() {
+    let parse = |drawing: &str| drawing.parse::<State>().unwrap_err();
+
+    assert_eq!(
+        parse("0 2\n0 N\nturn: A\nhand A:\nhand B:"),
+        ParseDiagramError::InvalidColumns { line: 1 }
+    );
+    assert_eq!(
+        parse("0 1\n0 N X\nturn: A\nhand A:\nhand B:"),
+        ParseDiagramError::InvalidCard { line: 2 }
+    );
+    assert_eq!(
+        parse("0 1\n0 N..\nturn: A\nhand A:\nhand B:"),
+        ParseDiagramError::TooManyCells { line: 2 }
+    );
+    assert_eq!(
+        parse("0 1\n0 N\n0 n\nturn: A\nhand A:\nhand B:"),
+        ParseDiagramError::DuplicateRow { line: 3 }
+    );
+    assert_eq!(
+        parse("0 1\n0 N\nhand A:\nhand B:"),
+        ParseDiagramError::MissingTurn
+    );
+    assert_eq!(
+        parse("turn: A\nhand B: Normal"),
+        ParseDiagramError::MissingHand(Player::A)
+    );
+    assert_eq!(
+        parse("turn: A\nhand A: Normal(0)\nhand B: Normal"),
+        ParseDiagramError::InvalidCard { line: 2 }
+    );
+}
+
+#[test]
+fn waiting_cards_are_drawn_with_their_turns_left() {
+    let drawing = "turn: B\nhand A: Ghost(2) Normal\nhand B:";
+    let state: State = drawing.parse().unwrap();
+
+    assert_eq!(
+        state.player_hand(Player::A),
+        [
+            HeldCard::Waiting {
+                card: CardType::Ghost,
+                turns_until_usable: 2,
+            },
+            HeldCard::Available(CardType::Normal),
+        ]
+    );
+    assert!(state.to_string().contains("hand A: Ghost(2) Normal\n"));
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::{hand, id_at, play_random_games, position};
+use lane_logic::{
+    card::CardType,
+    encoding::{decode, encode, DecodeError},
+    rules::RuleSet,
+    validate::{ValidationError, ValidationReport},
+    Player, Position, State,
+};
+
+/// CRC-16/CCITT-FALSE, the same as the encoding uses
+fn checksum(data: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+    for &byte in data {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn with_checksum(mut bytes: Vec<u8>) -> Vec<u8> {
+    let crc = checksum(&bytes);
+    bytes.extend_from_slice(&crc.to_be_bytes());
+    bytes
+}
+
+#[test]
+fn states_round_trip() {
+    let rules = RuleSet {
+        max_turns: Some(40),
+        ..RuleSet::default()
+    };
+
+    play_random_games(
+        0xe4c0,
+        20,
+        40,
+        || State::new(hand(), hand(), Player::B, rules.clone()),
+        |state, m| {
+            let bytes = encode(state).unwrap();
+            let decoded = decode(&bytes).unwrap();
+
+            assert_eq!(encode(&decoded).unwrap(), bytes);
+            assert_eq!(decoded.hash(), state.hash());
+            assert_eq!(decoded.turns_played(), state.turns_played());
+            assert_eq!(decoded.rules(), state.rules());
+            for (_, card) in state.board_state() {
+                let (_, decoded_card) = decoded.card_by_id(card.id).unwrap();
+                assert_eq!(decoded_card.position, card.position);
+            }
+            assert_eq!(
+                decoded.enumerate_possible_moves().len(),
+                state.enumerate_possible_moves().len()
+            );
+
+            state.execute_move(m)
+        },
+    );
+}
+
+#[test]
+fn encoding_is_small() {
+    let hand = hand()[..5].to_vec();
+    let state = State::new(hand.clone(), hand, Player::A, RuleSet::default());
+    // a version byte, two bytes of checksum and 126 bits of state with five
+    // cards in each hand
+    assert_eq!(encode(&state).unwrap().len(), 19);
+}
+
+#[test]
+fn corruption_is_detected() {
+    let state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    let mut bytes = encode(&state).unwrap();
+    bytes[3] ^= 0x10;
+
+    assert_eq!(decode(&bytes).unwrap_err(), DecodeError::ChecksumMismatch);
+    assert_eq!(decode(&bytes[..1]).unwrap_err(), DecodeError::UnexpectedEnd);
+    assert_eq!(
+        decode(&[3, 0, 0, 0]).unwrap_err(),
+        DecodeError::UnsupportedVersion(3)
+    );
+}
+
+fn push_bits(bits: &mut Vec<u8>, value: u32, count: u32) {
+    for i in 0..count {
+        bits.push(((value >> i) & 1) as u8);
+    }
+}
+
+fn push_number(bits: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let more = value >= 8;
+        push_bits(bits, value & 7 | u32::from(more) << 3, 4);
+        value >>= 3;
+        if !more {
+            return;
+        }
+    }
+}
+
+#[test]
+fn overlapping_cards_are_rejected() {
+    let mut bits = Vec::new();
+    push_bits(&mut bits, 0, 1);
+    push_number(&mut bits, 0);
+    push_bits(&mut bits, 1, 1);
+    push_number(&mut bits, 0);
+    push_number(&mut bits, 0);
+
+    // two score cards, where the gap from the first to the second wraps
+    // round to put them in the same place
+    push_number(&mut bits, 2);
+    push_number(&mut bits, 2);
+    push_bits(&mut bits, 4, 3);
+    push_bits(&mut bits, 0, 2);
+    push_number(&mut bits, 0);
+    push_number(&mut bits, 0);
+    push_number(&mut bits, 0);
+    push_bits(&mut bits, 4, 3);
+    push_bits(&mut bits, 0, 2);
+    push_number(&mut bits, 0);
+    push_number(&mut bits, u32::MAX);
+    push_number(&mut bits, 1);
+
+    let mut bytes = vec![2];
+    bytes.extend(bits.chunks(8).map(|chunk| {
+        chunk
+            .iter()
+            .enumerate()
+            .fold(0, |byte, (i, &bit)| byte | bit << i)
+    }));
+
+    assert_eq!(
+        decode(&with_checksum(bytes)).unwrap_err(),
+        DecodeError::OverlappingCards(Position((0, 0).into()))
+    );
+}
+
+#[test]
+fn invalid_states_are_rejected() {
+    // everything reads fine, but score cards belong to nobody
+    let mut rules = RuleSet::default();
+    rules
+        .starting_layout
+        .push((position(5, 5), CardType::Score, Some(Player::A)));
+    let state = State::new(hand(), hand(), Player::A, rules);
+
+    assert_eq!(
+        decode(&encode(&state).unwrap()).unwrap_err(),
+        DecodeError::InvalidState(ValidationReport {
+            errors: vec![ValidationError::OwnedScoreCard {
+                card: id_at(&state, 5, 5),
+                owner: Player::A,
+            }]
+        })
+    );
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+mod common;
+
+use common::{hand, play_random_games};
+use lane_logic::{
+    card::CardType, rules::RuleSet, BoardEvent, CardId, Direction, HeldCard, HeldCardIndex, Move,
+    PlaceCardMove, Player, Position, State,
+};
+
+fn positions(state: &State) -> HashMap<CardId, Position> {
+    state
+        .board_state()
+        .map(|(_, card)| (card.id, card.position))
+        .collect()
+}
+
+#[test]
+fn replaying_events_gives_the_new_board() {
+    play_random_games(
+        0xe7e,
+        50,
+        40,
+        || State::new(hand(), hand(), Player::A, RuleSet::default()),
+        |state, m| {
+            let mut board = positions(state);
+            let score_before = state.scores();
+            let result = state.execute_move(m);
+
+            let mut step = 0;
+            let mut before_captures = None;
+            for event in &result.events {
+                assert!(event.step() >= step, "events out of order");
+                step = event.step();
+
+                match event {
+                    BoardEvent::Placed { card, position, .. } => {
+                        assert!(board.insert(*card, *position).is_none());
+                    }
+                    BoardEvent::Moved { card, from, to, .. } => {
+                        assert_eq!(board.insert(*card, *to), Some(*from));
+                    }
+                    BoardEvent::Captured { card, by, .. } => {
+                        // cards are captured all at once, so capturing cards
+                        // can be captured themselves
+                        let before_captures = before_captures.get_or_insert_with(|| board.clone());
+                        assert!(!by.is_empty());
+                        assert!(by.iter().all(|by| before_captures.contains_key(by)));
+                        assert!(board.remove(card).is_some());
+                    }
+                    BoardEvent::ReturnedToHand { card, .. } => {
+                        board.remove(card);
+                    }
+                    BoardEvent::ScoreChanged {
+                        player, from, to, ..
+                    } => {
+                        assert_eq!(*from, score_before.player(*player));
+                        assert_eq!(*to, result.score.player(*player));
+                    }
+                }
+            }
+
+            assert_eq!(board, positions(state));
+            result
+        },
+    );
+}
+
+#[test]
+fn double_pushes_happen_one_after_the_other() {
+    let mut rules = RuleSet::default();
+    rules
+        .starting_layout
+        .push((Position((1, 1).into()), CardType::Normal, Some(Player::B)));
+    let mut state = State::new(
+        vec![HeldCard::Available(CardType::Double)],
+        hand(),
+        Player::A,
+        rules,
+    );
+
+    let result = state
+        .try_execute_move(&Move::PlaceCard(PlaceCardMove {
+            direction: Direction::East,
+            coordinate: Position((0, 1).into()),
+            card: HeldCardIndex(0),
+        }))
+        .unwrap();
+
+    let (_, _, double) = &result.placed[0];
+    let steps: Vec<_> = result
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            BoardEvent::Moved { card, to, step, .. } if *card == double.id => Some((*to, *step)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        steps,
+        [(Position((1, 1).into()), 1), (Position((2, 1).into()), 2)]
+    );
+}
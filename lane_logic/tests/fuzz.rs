@@ -0,0 +1,339 @@
+//! Plays random games and checks the rules engine's invariants after every
+//! move. When a game breaks one, proptest shrinks it down to the fewest moves
+//! and smallest hands and layout which still do, and the failure gives the
+//! starting drawing and the moves in notation so it can be replayed.
+//!
+//! Set `PROPTEST_CASES` to play more than the default 256 games.
+
+use std::collections::HashSet;
+
+mod common;
+
+use common::{
+    position,
+    strategies::{direction, hand, layout},
+};
+use lane_logic::{
+    card::CardType, notation::MoveNotation, rules::RuleSet, Direction, HeldCard, HeldCardIndex,
+    Move, MoveResult, PickCardMove, PlaceCardMove, Player, PushCardMove, State,
+};
+use proptest::{prelude::*, sample::Index, test_runner::TestCaseError};
+
+/// A move which may or may not be legal, used to check that the legality
+/// checks agree with actually making the move
+#[derive(Debug, Clone)]
+enum Probe {
+    Place {
+        slot: Index,
+        offset: (i32, i32),
+        direction: Direction,
+    },
+    Push {
+        card: Index,
+        direction: Direction,
+    },
+    Pick {
+        card: Index,
+    },
+}
+
+fn probe() -> impl Strategy<Value = Probe> {
+    prop_oneof![
+        (any::<Index>(), (-2..3, -2..3), direction()).prop_map(|(slot, offset, direction)| {
+            Probe::Place {
+                slot,
+                offset,
+                direction,
+            }
+        }),
+        (any::<Index>(), direction()).prop_map(|(card, direction)| Probe::Push { card, direction }),
+        any::<Index>().prop_map(|card| Probe::Pick { card }),
+    ]
+}
+
+impl Probe {
+    /// Turns the probe into a move in this state. Places are next to a card
+    /// already on the board, and can refer to one past the end of the hand.
+    fn to_move(&self, state: &State) -> Move {
+        let cards: Vec<_> = state.board_state().collect();
+
+        match self {
+            Probe::Place {
+                slot,
+                offset: (x, y),
+                direction,
+            } => {
+                let (_, card) = slot.get(&cards);
+                Move::PlaceCard(PlaceCardMove {
+                    direction: *direction,
+                    coordinate: position(card.position.0.x + x, card.position.0.y + y),
+                    card: HeldCardIndex(slot.index(state.turn_hand().len() + 1)),
+                })
+            }
+            Probe::Push { card, direction } => Move::PushCard(PushCardMove {
+                place: card.get(&cards).1.id,
+                direction: *direction,
+            }),
+            Probe::Pick { card } => Move::PickCard(PickCardMove {
+                card: card.get(&cards).1.id,
+            }),
+        }
+    }
+}
+
+fn held_type(card: &HeldCard) -> CardType {
+    match card {
+        HeldCard::Available(card) | HeldCard::Waiting { card, .. } => *card,
+    }
+}
+
+/// The types of the cards each player has, on the board or in their hand
+fn cards_owned(state: &State) -> [Vec<&'static str>; 2] {
+    let mut owned = [Vec::new(), Vec::new()];
+    for (_, card) in state.board_state() {
+        if let Some(player) = card.belonging_player {
+            owned[player as usize].push(card.card.to_type().name());
+        }
+    }
+    for player in [Player::A, Player::B] {
+        owned[player as usize].extend(
+            state
+                .player_hand(player)
+                .iter()
+                .map(|card| held_type(card).name()),
+        );
+        owned[player as usize].sort();
+    }
+    owned
+}
+
+fn unowned_cards(state: &State) -> usize {
+    state
+        .board_state()
+        .filter(|(_, card)| card.belonging_player.is_none())
+        .count()
+}
+
+/// Works the score out again from scratch: every owned card next to a score
+/// card counts once for its owner
+fn expected_score(state: &State) -> [usize; 2] {
+    let mut scoring = HashSet::new();
+    for (_, card) in state.board_state() {
+        if card.card.to_type() != CardType::Score {
+            continue;
+        }
+        for direction in [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ] {
+            if let Some((idx, next)) = state.card_at_position(card.position + direction) {
+                if let Some(player) = next.belonging_player {
+                    scoring.insert((idx, player as usize));
+                }
+            }
+        }
+    }
+
+    let mut score = [0, 0];
+    for (_, player) in scoring {
+        score[player] += 1;
+    }
+    score
+}
+
+fn check_positions(state: &State) -> Result<(), TestCaseError> {
+    let mut positions = HashSet::new();
+    for (idx, card) in state.board_state() {
+        prop_assert!(
+            positions.insert(card.position),
+            "two cards at {}",
+            card.position
+        );
+        prop_assert_eq!(
+            state.card_at_position(card.position).map(|(at, _)| at),
+            Some(idx)
+        );
+    }
+    Ok(())
+}
+
+fn check_move(
+    before: &State,
+    m: &Move,
+    result: &MoveResult,
+    after: &State,
+) -> Result<(), TestCaseError> {
+    check_positions(after)?;
+    prop_assert_eq!(after.validate(), Ok(()));
+
+    // cards only change hands by being captured, and captured cards go back to
+    // their owner, so each player keeps the same cards. Cards belonging to
+    // nobody can only be captured.
+    prop_assert_eq!(cards_owned(after), cards_owned(before));
+    let unowned_removed = result
+        .removed
+        .iter()
+        .filter(|(_, card)| card.belonging_player.is_none())
+        .count();
+    prop_assert_eq!(
+        unowned_cards(after),
+        unowned_cards(before) - unowned_removed
+    );
+
+    let score = [
+        after.scores().player(Player::A),
+        after.scores().player(Player::B),
+    ];
+    prop_assert_eq!(
+        score,
+        expected_score(after),
+        "the score doesn't match the board"
+    );
+    prop_assert_eq!(
+        [
+            result.score.player(Player::A),
+            result.score.player(Player::B)
+        ],
+        score,
+        "the move reported a different score"
+    );
+
+    let simulated = before.simulate(m);
+    prop_assert!(simulated.is_ok(), "simulating {:?} failed", m);
+    let simulated = simulated.unwrap();
+    prop_assert_eq!(
+        simulated.score.player(Player::A),
+        result.score.player(Player::A)
+    );
+    prop_assert_eq!(
+        simulated.score.player(Player::B),
+        result.score.player(Player::B)
+    );
+
+    Ok(())
+}
+
+/// Two moves are the same if they only differ in which of two cards of the
+/// same type is placed
+fn same_move(state: &State, a: &Move, b: &Move) -> bool {
+    match (a, b) {
+        (Move::PlaceCard(a), Move::PlaceCard(b)) => {
+            let hand = state.turn_hand();
+            a.coordinate == b.coordinate
+                && a.direction == b.direction
+                && hand.get(a.card.0) == hand.get(b.card.0)
+        }
+        _ => a == b,
+    }
+}
+
+fn check_probe(state: &State, moves: &[Move], probe: &Move) -> Result<(), TestCaseError> {
+    let mut tried = state.clone();
+    let executed = tried.try_execute_move(probe);
+
+    prop_assert_eq!(
+        state.can_execute_move(probe),
+        executed.is_ok(),
+        "can_execute_move disagrees with executing {:?}: {:?}",
+        probe,
+        executed.as_ref().err()
+    );
+    prop_assert_eq!(
+        state.check_move(probe).err(),
+        executed.as_ref().err().cloned()
+    );
+    prop_assert_eq!(
+        moves.iter().any(|m| same_move(state, m, probe)),
+        executed.is_ok(),
+        "{:?} is legal but not enumerated, or the other way round",
+        probe
+    );
+    if executed.is_err() {
+        prop_assert_eq!(
+            tried.hash(),
+            state.hash(),
+            "a failed move changed the state"
+        );
+    }
+
+    Ok(())
+}
+
+fn play(
+    state: &mut State,
+    steps: &[(Index, Probe)],
+    played: &mut Vec<String>,
+) -> Result<(), TestCaseError> {
+    check_positions(state)?;
+
+    for (choice, probe) in steps {
+        let moves = state.enumerate_possible_moves();
+        if moves.is_empty() {
+            break;
+        }
+
+        if state.board_state().next().is_some() {
+            check_probe(state, &moves, &probe.to_move(state))?;
+        }
+
+        let m = choice.get(&moves);
+        prop_assert!(
+            state.can_execute_move(m),
+            "{:?} was enumerated but is illegal",
+            m
+        );
+        played.push(MoveNotation::from_move(state, m).unwrap().to_string());
+
+        let before = state.clone();
+        let result = state.try_execute_move(m);
+        prop_assert!(
+            result.is_ok(),
+            "{:?} was enumerated but failed: {:?}",
+            m,
+            result
+        );
+        let result = result.unwrap();
+        check_move(&before, m, &result, state)?;
+
+        if result.outcome.is_over() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn random_games_keep_the_invariants(
+        hand_a in hand(),
+        hand_b in hand(),
+        layout in layout(10),
+        connected in any::<bool>(),
+        steps in prop::collection::vec((any::<Index>(), probe()), 1..40),
+    ) {
+        let mut rules = RuleSet {
+            connected,
+            ..RuleSet::default()
+        };
+        rules.starting_layout.extend(layout);
+        let mut state = State::new(hand_a.clone(), hand_b.clone(), Player::A, rules.clone());
+
+        // the connected rule only applies if the cards start off connected
+        if state.validate().is_err() {
+            rules.connected = false;
+            state = State::new(hand_a, hand_b, Player::A, rules);
+        }
+        let start = state.to_string();
+
+        let mut played = Vec::new();
+        if let Err(error) = play(&mut state, &steps, &mut played) {
+            return Err(TestCaseError::fail(format!(
+                "{error}\n\nstarting from\n{start}\nafter {}",
+                played.join(", ")
+            )));
+        }
+    }
+}
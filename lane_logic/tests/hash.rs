@@ -0,0 +1,109 @@
+mod common;
+
+use common::{hand, play_random_games, position};
+use lane_logic::{
+    card::CardType,
+    encoding::{decode, encode},
+    rules::RuleSet,
+    Direction, Move, Player, PushCardMove, State,
+};
+
+/// The hash of the same state built again from nothing, by encoding it and
+/// decoding it again
+fn hash_from_scratch(state: &State) -> u64 {
+    decode(&encode(state).unwrap()).unwrap().hash()
+}
+
+#[test]
+fn hashes_kept_up_to_date_match_hashes_from_scratch() {
+    play_random_games(
+        0x4a54,
+        20,
+        40,
+        || State::new(hand(), hand(), Player::A, RuleSet::default()),
+        |state, m| {
+            assert_eq!(state.hash(), hash_from_scratch(state));
+
+            // every other move is made and taken back first
+            for m in state.enumerate_possible_moves().iter().step_by(2) {
+                let (_, undo) = state.execute_move_with_undo(m);
+                assert_eq!(state.hash(), hash_from_scratch(state), "after {m:?}");
+                state.undo_move(undo);
+                assert_eq!(state.hash(), hash_from_scratch(state), "undoing {m:?}");
+            }
+
+            let result = state.execute_move(m);
+            assert_eq!(state.hash(), hash_from_scratch(state));
+            result
+        },
+    );
+}
+
+#[test]
+fn moving_the_whole_board_keeps_the_hash() {
+    let drawing = |x: i32, y: i32| {
+        format!(
+            "
+                {}   {}   {}
+            {}   N  (S) (S)
+            {}   .   g   .
+            turn: B
+            hand A: Normal Ghost(1)
+            hand B: Double
+            ",
+            x,
+            x + 1,
+            x + 2,
+            y,
+            y + 1
+        )
+        .parse::<State>()
+        .unwrap()
+    };
+
+    let hash = drawing(0, 0).hash();
+    for (x, y) in [(-1, 0), (3, 0), (0, -2), (5, 7), (-40, 12)] {
+        assert_eq!(drawing(x, y).hash(), hash, "moved to {x},{y}");
+    }
+
+    // but not a change to the board itself
+    assert_ne!(
+        "
+               0   1   2
+           0   N  (S) (S)
+           1   g   .   .
+        turn: B
+        hand A: Normal Ghost(1)
+        hand B: Double
+        "
+        .parse::<State>()
+        .unwrap()
+        .hash(),
+        hash
+    );
+}
+
+#[test]
+fn pushing_the_whole_board_along_keeps_the_hash() {
+    // pushing every card on the board one step gives the same position
+    let mut rules = RuleSet::default();
+    rules
+        .starting_layout
+        .push((position(-1, 0), CardType::Normal, Some(Player::A)));
+    let mut state = State::new(hand(), hand(), Player::A, rules.clone());
+
+    let own = state.card_at_position(position(-1, 0)).unwrap().1.id;
+    state
+        .try_execute_move(&Move::PushCard(PushCardMove {
+            place: own,
+            direction: Direction::East,
+        }))
+        .unwrap();
+
+    let mut moved = rules;
+    for (position, _, _) in &mut moved.starting_layout {
+        position.0.x += 1;
+    }
+    let expected = State::new(hand(), hand(), Player::B, moved);
+    assert_eq!(state.hash(), expected.hash());
+}
@@ -0,0 +1,219 @@
+//! Which error each kind of illegal move is reported with.
+
+mod common;
+
+use common::position;
+use lane_logic::{
+    card::CardType, notation::MoveNotation, CardId, Direction, Move, MoveError, PickCardMove,
+    Player, PushCardMove, State,
+};
+
+fn state(drawing: &str) -> State {
+    drawing.parse().unwrap()
+}
+
+fn id_at(state: &State, x: i32, y: i32) -> CardId {
+    state.card_at_position(position(x, y)).unwrap().1.id
+}
+
+/// The error the move fails with, checking that every way of trying the move
+/// gives the same error and leaves the state as it was
+fn error(state: &State, m: &Move) -> MoveError {
+    let error = state.check_move(m).unwrap_err();
+    assert!(!state.can_execute_move(m));
+    assert_eq!(state.simulate(m).unwrap_err(), error);
+
+    let mut tried = state.clone();
+    assert_eq!(tried.try_execute_move(m).unwrap_err(), error);
+    assert_eq!(tried.hash(), state.hash());
+    assert_eq!(tried.to_string(), state.to_string());
+
+    error
+}
+
+fn error_for(state: &State, m: &str) -> MoveError {
+    let m = m.parse::<MoveNotation>().unwrap().resolve(state).unwrap();
+    error(state, &m)
+}
+
+const BOARD: &str = "
+       -1   0   1   2
+   0    N (S) (S)   n
+turn: A
+hand A: Normal Score Ghost(2)
+hand B: Normal
+";
+
+#[test]
+fn held_card_out_of_range() {
+    assert_eq!(
+        error_for(&state(BOARD), "P3@-1,1<E"),
+        MoveError::HeldCardOutOfRange {
+            index: 3,
+            hand_size: 3,
+        }
+    );
+}
+
+#[test]
+fn card_waiting() {
+    assert_eq!(
+        error_for(&state(BOARD), "P2@-1,1<E"),
+        MoveError::CardWaiting {
+            card: CardType::Ghost,
+            turns_until_usable: 2,
+        }
+    );
+}
+
+#[test]
+fn card_not_placeable() {
+    assert_eq!(
+        error_for(&state(BOARD), "P1@-1,1<E"),
+        MoveError::CardNotPlaceable(CardType::Score)
+    );
+}
+
+#[test]
+fn position_occupied() {
+    assert_eq!(
+        error_for(&state(BOARD), "P0@-1,0<E"),
+        MoveError::PositionOccupied(position(-1, 0))
+    );
+}
+
+#[test]
+fn not_an_edge() {
+    // the normal card is behind, so this isn't the end of the lane
+    assert_eq!(
+        error_for(&state(BOARD), "P0@-2,0<W"),
+        MoveError::NotAnEdge {
+            position: position(-2, 0),
+            direction: Direction::West,
+        }
+    );
+}
+
+#[test]
+fn no_adjacent_card() {
+    assert_eq!(
+        error_for(&state(BOARD), "P0@5,5<N"),
+        MoveError::NoAdjacentCard {
+            position: position(5, 5),
+            direction: Direction::North,
+        }
+    );
+}
+
+#[test]
+fn card_not_found() {
+    let m = Move::PushCard(PushCardMove {
+        place: CardId(1000),
+        direction: Direction::East,
+    });
+    assert_eq!(
+        error(&state(BOARD), &m),
+        MoveError::CardNotFound(CardId(1000))
+    );
+
+    let m = Move::PickCard(PickCardMove { card: CardId(1000) });
+    assert_eq!(
+        error(&state(BOARD), &m),
+        MoveError::CardNotFound(CardId(1000))
+    );
+}
+
+#[test]
+fn not_owned() {
+    let state = state(BOARD);
+
+    assert_eq!(
+        error_for(&state, "push 2,0 W"),
+        MoveError::NotOwned {
+            card: id_at(&state, 2, 0),
+            owner: Some(Player::B),
+        }
+    );
+    assert_eq!(
+        error_for(&state, "pick 0,0"),
+        MoveError::NotOwned {
+            card: id_at(&state, 0, 0),
+            owner: None,
+        }
+    );
+}
+
+#[test]
+fn nothing_to_push() {
+    let state = state(BOARD);
+
+    // nothing in front at all
+    assert_eq!(
+        error_for(&state, "push -1,0 N"),
+        MoveError::NothingToPush {
+            card: id_at(&state, -1, 0),
+            direction: Direction::North,
+        }
+    );
+}
+
+#[test]
+fn push_blocked() {
+    let state = state(
+        "
+           -1   0
+       0    N  (B)
+    turn: A
+    hand A:
+    hand B:
+    ",
+    );
+
+    assert_eq!(
+        error_for(&state, "push -1,0 E"),
+        MoveError::PushBlocked {
+            card: id_at(&state, -1, 0),
+            blocked_by: id_at(&state, 0, 0),
+        }
+    );
+}
+
+#[test]
+fn push_cycle() {
+    // bounced back by one reverse card into the other, which would send it
+    // back a second time
+    let state = state(
+        "
+           -1   0   1
+       0   (V)  N  (V)
+    turn: A
+    hand A:
+    hand B:
+    ",
+    );
+    let own = id_at(&state, 0, 0);
+
+    assert_eq!(
+        error_for(&state, "push 0,0 E"),
+        MoveError::PushCycle {
+            card: own,
+            through: own,
+        }
+    );
+}
+
+#[test]
+fn disconnects_board() {
+    let state = state(
+        "
+           -1   0   1
+       0  (S)   N  (S)
+    turn: A
+    hand A:
+    hand B:
+    rules: connected=yes
+    ",
+    );
+
+    assert_eq!(error_for(&state, "pick 0,0"), MoveError::DisconnectsBoard);
+}
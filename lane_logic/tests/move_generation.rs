@@ -0,0 +1,57 @@
+mod common;
+
+use common::{hand, play_random_games};
+use lane_logic::{card::CardType, rules::RuleSet, HeldCard, Move, Player, State};
+
+#[test]
+fn every_generated_move_is_legal() {
+    play_random_games(
+        0x1a2e,
+        100,
+        60,
+        || State::new(hand(), hand(), Player::A, RuleSet::default()),
+        |state, m| {
+            for m in &state.enumerate_possible_moves() {
+                assert!(
+                    state.can_execute_move(m),
+                    "generated an illegal move: {:?}",
+                    state.check_move(m)
+                );
+            }
+
+            state.execute_move(m)
+        },
+    );
+}
+
+#[test]
+fn placing_cards_of_the_same_type_is_only_generated_once() {
+    let state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    let hand = state.turn_hand();
+
+    let mut placements: Vec<_> = state
+        .enumerate_possible_moves()
+        .into_iter()
+        .filter_map(|m| match m {
+            Move::PlaceCard(place) => {
+                let card = match hand[place.card.0] {
+                    HeldCard::Available(card) => card,
+                    HeldCard::Waiting { .. } => panic!("placed a waiting card"),
+                };
+                Some((card, place.coordinate, place.direction))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let total = placements.len();
+    placements.sort_by_key(|(card, position, direction)| {
+        (card.name(), position.0.x, position.0.y, *direction as usize)
+    });
+    placements.dedup();
+
+    assert_eq!(placements.len(), total);
+    assert!(placements
+        .iter()
+        .any(|&(card, _, _)| card == CardType::Normal));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{hand, play_random_games, position};
+use lane_logic::{
+    notation::{MoveNotation, ParseError},
+    rules::RuleSet,
+    Direction, MoveError, Player, State,
+};
+
+#[test]
+fn moves_are_written_and_read_back() {
+    let moves = [
+        (
+            MoveNotation::Place {
+                card: 2,
+                position: position(-1, 0),
+                direction: Direction::East,
+            },
+            "P2@-1,0<E",
+        ),
+        (
+            MoveNotation::Push {
+                position: position(3, -12),
+                direction: Direction::North,
+            },
+            "push 3,-12 N",
+        ),
+        (
+            MoveNotation::Pick {
+                position: position(0, 7),
+            },
+            "pick 0,7",
+        ),
+    ];
+
+    for (notation, text) in moves {
+        assert_eq!(notation.to_string(), text);
+        assert_eq!(text.parse::<MoveNotation>(), Ok(notation));
+    }
+}
+
+#[test]
+fn long_forms_and_any_case_are_read() {
+    let parse = |text: &str| text.parse::<MoveNotation>().unwrap();
+
+    assert_eq!(parse("place 2 -1,0 east"), parse("P2@-1,0<E"));
+    assert_eq!(parse("p2@-1, 0<e"), parse("P2@-1,0<E"));
+    assert_eq!(parse("  PUSH 3,-12 North "), parse("push 3,-12 N"));
+    assert_eq!(parse("Pick 0,7"), parse("pick 0,7"));
+}
+
+#[test]
+fn broken_moves_are_reported() {
+    let parse = |text: &str| text.parse::<MoveNotation>().unwrap_err();
+
+    assert_eq!(parse("shove 0,0 E"), ParseError::UnknownMove);
+    assert_eq!(parse(""), ParseError::MissingInput);
+    assert_eq!(parse("place x 0,0 E"), ParseError::InvalidHandIndex);
+    assert_eq!(parse("P1@0,0"), ParseError::MissingInput);
+    assert_eq!(parse("P1@0,0<"), ParseError::MissingInput);
+    assert_eq!(parse("P1@0;0<E"), ParseError::InvalidPosition);
+    assert_eq!(parse("push 0 E"), ParseError::InvalidPosition);
+    assert_eq!(parse("push 0,0 up"), ParseError::InvalidDirection);
+    assert_eq!(parse("push 0,0"), ParseError::MissingInput);
+    assert_eq!(parse("pick 0,0 E"), ParseError::TrailingInput);
+    assert_eq!(parse("P2@3,-1<W extra"), ParseError::TrailingInput);
+}
+
+#[test]
+fn notation_resolves_to_the_same_move() {
+    play_random_games(
+        0x407a,
+        20,
+        40,
+        || State::new(hand(), hand(), Player::A, RuleSet::default()),
+        |state, m| {
+            let notation = MoveNotation::from_move(state, m).unwrap();
+            let read: MoveNotation = notation.to_string().parse().unwrap();
+            assert_eq!(read, notation);
+            assert_eq!(read.resolve(state).as_ref(), Some(m));
+
+            state.execute_move(m)
+        },
+    );
+}
+
+#[test]
+fn moves_on_empty_positions_do_not_resolve() {
+    let state = State::new(hand(), hand(), Player::A, RuleSet::default());
+
+    for text in ["push 5,5 E", "pick 5,5"] {
+        let notation: MoveNotation = text.parse().unwrap();
+        assert_eq!(notation.resolve(&state), None);
+    }
+}
+
+#[test]
+fn positions_in_errors_are_written_like_notation() {
+    assert_eq!(
+        MoveError::PositionOccupied(position(-1, 2)).to_string(),
+        "-1,2 is already occupied"
+    );
+}
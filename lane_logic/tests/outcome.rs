@@ -0,0 +1,56 @@
+use lane_logic::{notation::MoveNotation, DrawReason, GameOutcome, MoveResult, State};
+
+/// Two cards either side of the score cards, which the players push back and
+/// forth so the cards end up where they started
+const BACK_AND_FORTH: &str = "
+       -1   0   1   2
+   0    N (S) (S)  n
+turn: A
+hand A: Normal
+hand B: Normal
+";
+
+fn play(state: &mut State, m: &str) -> MoveResult {
+    let m = m.parse::<MoveNotation>().unwrap().resolve(state).unwrap();
+    state.try_execute_move(&m).unwrap()
+}
+
+#[test]
+fn the_third_repetition_is_a_draw() {
+    let mut state: State = BACK_AND_FORTH.parse().unwrap();
+    let start = state.hash();
+
+    for round in 1..=2 {
+        assert_eq!(
+            play(&mut state, "push -1,0 E").outcome,
+            GameOutcome::Ongoing
+        );
+        let outcome = play(&mut state, "push 3,0 W").outcome;
+
+        assert_eq!(state.hash(), start);
+        assert_eq!(state.repetitions(), round + 1);
+        if round == 1 {
+            assert_eq!(outcome, GameOutcome::Ongoing);
+        } else {
+            assert_eq!(outcome, GameOutcome::Draw(DrawReason::Repetition));
+        }
+    }
+}
+
+#[test]
+fn reaching_the_turn_limit_is_a_draw() {
+    let mut state: State = format!("{BACK_AND_FORTH}rules: max_turns=3")
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        play(&mut state, "push -1,0 E").outcome,
+        GameOutcome::Ongoing
+    );
+    assert_eq!(play(&mut state, "push 3,0 W").outcome, GameOutcome::Ongoing);
+    assert_eq!(
+        play(&mut state, "push -1,0 E").outcome,
+        GameOutcome::Draw(DrawReason::TurnLimit)
+    );
+    assert_eq!(state.turns_played(), 3);
+}
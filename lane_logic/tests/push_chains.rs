@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+
+mod common;
+
+use common::{
+    position,
+    strategies::{hand, layout},
+};
+use lane_logic::{
+    card::CardType, rules::RuleSet, Direction, HeldCard, HeldCardIndex, Move, MoveError,
+    PickCardMove, PlaceCardMove, Player, PushCardMove, State,
+};
+use proptest::prelude::*;
+
+fn assert_no_overlaps(state: &State) {
+    let mut positions = HashSet::new();
+    for (idx, card) in state.board_state() {
+        assert!(
+            positions.insert(card.position),
+            "two cards at {}",
+            card.position
+        );
+        assert_eq!(state.card_at_position(card.position).unwrap().0, idx);
+    }
+}
+
+/// The cards on the board without their indices or ids, which depend on the
+/// order the cards were put down
+fn board(state: &State) -> Vec<(i32, i32, &'static str, Option<u8>)> {
+    let mut board: Vec<_> = state
+        .board_state()
+        .map(|(_, card)| {
+            (
+                card.position.0.x,
+                card.position.0.y,
+                card.card.to_type().name(),
+                card.belonging_player.map(|player| player as u8),
+            )
+        })
+        .collect();
+    board.sort();
+    board
+}
+
+/// The same move in another state with the same board, finding the card to
+/// move by its position
+fn translate(m: &Move, from: &State, to: &State) -> Move {
+    let same_card = |id| {
+        to.card_at_position(from.card_by_id(id).unwrap().1.position)
+            .unwrap()
+            .1
+            .id
+    };
+
+    match m {
+        Move::PushCard(push) => Move::PushCard(PushCardMove {
+            place: same_card(push.place),
+            direction: push.direction,
+        }),
+        Move::PickCard(pick) => Move::PickCard(PickCardMove {
+            card: same_card(pick.card),
+        }),
+        Move::PlaceCard(_) => m.clone(),
+    }
+}
+
+proptest! {
+    #[test]
+    fn cards_never_share_a_position(
+        hand_a in hand(),
+        hand_b in hand(),
+        layout in layout(12),
+        choices in prop::collection::vec(any::<prop::sample::Index>(), 1..30),
+    ) {
+        let mut rules = RuleSet::default();
+        rules.starting_layout.extend(layout);
+        let mut state = State::new(hand_a, hand_b, Player::A, rules);
+        assert_no_overlaps(&state);
+
+        for choice in choices {
+            let moves = state.enumerate_possible_moves();
+            if moves.is_empty() {
+                break;
+            }
+
+            let before = board(&state);
+            let (_, undo) = state.execute_move_with_undo(choice.get(&moves));
+            assert_no_overlaps(&state);
+
+            state.undo_move(undo);
+            assert_no_overlaps(&state);
+            prop_assert_eq!(board(&state), before);
+
+            if state.execute_move(choice.get(&moves)).outcome.is_over() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn pushes_do_not_depend_on_the_order_cards_were_added(
+        hand_a in hand(),
+        hand_b in hand(),
+        layout in layout(12),
+        choices in prop::collection::vec(any::<prop::sample::Index>(), 1..20),
+    ) {
+        let mut rules = RuleSet::default();
+        rules.starting_layout.extend(layout);
+        let mut state = State::new(hand_a.clone(), hand_b.clone(), Player::A, rules.clone());
+        rules.starting_layout.reverse();
+        let mut reversed = State::new(hand_a, hand_b, Player::A, rules);
+
+        for choice in choices {
+            prop_assert_eq!(board(&state), board(&reversed));
+
+            let moves = state.enumerate_possible_moves();
+            prop_assert_eq!(moves.len(), reversed.enumerate_possible_moves().len());
+            if moves.is_empty() {
+                break;
+            }
+
+            let m = choice.get(&moves);
+            let reversed_move = translate(m, &state, &reversed);
+            let outcome = state.execute_move(m).outcome;
+            prop_assert_eq!(reversed.execute_move(&reversed_move).outcome, outcome);
+
+            if outcome.is_over() {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn pushing_into_a_reverse_card_bounces_back() {
+    let mut rules = RuleSet::default();
+    rules.starting_layout.extend([
+        (position(0, 3), CardType::Normal, Some(Player::A)),
+        (position(1, 3), CardType::Reverse, None),
+    ]);
+    let mut state = State::new(
+        vec![HeldCard::Available(CardType::Normal)],
+        vec![HeldCard::Available(CardType::Normal)],
+        Player::A,
+        rules,
+    );
+    let own = state.card_at_position(position(0, 3)).unwrap().1.id;
+    let reverse = state.card_at_position(position(1, 3)).unwrap().1.id;
+
+    // the reverse card pushes the card pushing it straight back, and follows
+    let result = state
+        .try_execute_move(&Move::PushCard(PushCardMove {
+            place: own,
+            direction: Direction::East,
+        }))
+        .unwrap();
+    assert_eq!(result.moved.len(), 2);
+    assert_eq!(state.card_by_id(own).unwrap().1.position, position(-1, 3));
+    assert_eq!(
+        state.card_by_id(reverse).unwrap().1.position,
+        position(0, 3)
+    );
+    assert_no_overlaps(&state);
+}
+
+#[test]
+fn pushing_round_in_a_circle_is_blocked() {
+    let mut rules = RuleSet::default();
+    rules.starting_layout.extend([
+        (position(-1, 3), CardType::Reverse, None),
+        (position(0, 3), CardType::Normal, Some(Player::A)),
+        (position(1, 3), CardType::Reverse, None),
+    ]);
+    let state = State::new(
+        vec![HeldCard::Available(CardType::Normal)],
+        vec![HeldCard::Available(CardType::Normal)],
+        Player::A,
+        rules,
+    );
+    let own = state.card_at_position(position(0, 3)).unwrap().1.id;
+
+    // bounced back by one reverse card into the other, which would send it
+    // back a second time
+    let push = Move::PushCard(PushCardMove {
+        place: own,
+        direction: Direction::East,
+    });
+    assert_eq!(
+        state.check_move(&push),
+        Err(MoveError::PushCycle {
+            card: own,
+            through: own,
+        })
+    );
+    assert_eq!(
+        state.simulate(&push).unwrap_err(),
+        state.check_move(&push).unwrap_err()
+    );
+
+    // a card placed against them only reaches the first reverse card, and
+    // bounces off it
+    let mut placed = state.clone();
+    let result = placed
+        .try_execute_move(&Move::PlaceCard(PlaceCardMove {
+            direction: Direction::East,
+            coordinate: position(-2, 3),
+            card: HeldCardIndex(0),
+        }))
+        .unwrap();
+    assert_eq!(result.moved.len(), 2);
+    assert_eq!(
+        board(&placed),
+        [
+            (-3, 3, "Normal", Some(0)),
+            (-2, 3, "Reverse", None),
+            (0, 0, "Score", None),
+            (0, 3, "Normal", Some(0)),
+            (1, 0, "Score", None),
+            (1, 3, "Reverse", None),
+        ]
+    );
+    assert_no_overlaps(&placed);
+}
+
+#[test]
+fn long_chains_can_be_pushed() {
+    let mut rules = RuleSet::default();
+    rules
+        .starting_layout
+        .extend((2..8).map(|x| (position(x, 0), CardType::Normal, Some(Player::B))));
+    rules
+        .starting_layout
+        .push((position(-1, 0), CardType::Normal, Some(Player::A)));
+    let mut state = State::new(
+        vec![HeldCard::Available(CardType::Normal)],
+        vec![HeldCard::Available(CardType::Normal)],
+        Player::A,
+        rules,
+    );
+    let own = state.card_at_position(position(-1, 0)).unwrap().1.id;
+
+    let push = Move::PushCard(PushCardMove {
+        place: own,
+        direction: Direction::East,
+    });
+    assert_eq!(state.try_execute_move(&push).unwrap().moved.len(), 9);
+}
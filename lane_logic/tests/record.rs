@@ -0,0 +1,213 @@
+mod common;
+
+use common::{hand, play_random_games};
+use lane_logic::{
+    card::CardType,
+    notation,
+    record::{GameRecord, ParseRecordError, Replay, ReplayError},
+    rules::RuleSet,
+    HeldCard, MoveError, Player, State,
+};
+
+/// Plays random games, giving the record of each and the hash after every move
+fn recorded_games() -> Vec<(GameRecord, Vec<u64>)> {
+    let rules = RuleSet {
+        default_cooldown: 2,
+        ..RuleSet::default()
+    };
+    let mut games: Vec<(GameRecord, Vec<u64>)> = Vec::new();
+
+    play_random_games(
+        0x7ec0,
+        20,
+        40,
+        || State::new(hand(), hand(), Player::A, rules.clone()),
+        |state, m| {
+            if state.turns_played() == 0 {
+                let mut record = GameRecord::new(hand(), hand(), Player::A, rules.clone());
+                record.seed = Some(games.len() as u64);
+                games.push((record, vec![state.hash()]));
+            }
+
+            let (record, hashes) = games.last_mut().unwrap();
+            record.record_move(state, m).unwrap();
+            let result = state.execute_move(m);
+            hashes.push(state.hash());
+            result
+        },
+    );
+
+    games
+}
+
+#[test]
+fn records_round_trip() {
+    for (record, _) in recorded_games() {
+        let text = record.to_string();
+        let parsed: GameRecord = text.parse().unwrap();
+
+        assert_eq!(parsed, record);
+        assert_eq!(parsed.to_string(), text);
+    }
+}
+
+#[test]
+fn waiting_cards_round_trip() {
+    let record = GameRecord::new(
+        vec![HeldCard::Waiting {
+            card: CardType::Ghost,
+            turns_until_usable: 3,
+        }],
+        hand(),
+        Player::B,
+        RuleSet::default(),
+    );
+
+    let text = record.to_string();
+    assert!(text.starts_with("hand A: Ghost(3)\n"));
+    assert_eq!(text.parse::<GameRecord>(), Ok(record));
+}
+
+#[test]
+fn broken_records_are_reported() {
+    let parse = |text: &str| text.parse::<GameRecord>().unwrap_err();
+
+    assert_eq!(
+        parse("hand A: Normal(0)\nhand B: Normal\nstart: A\nmoves:"),
+        ParseRecordError::InvalidCard { line: 1 }
+    );
+    assert_eq!(
+        parse("hand A: Normal\nhand B: Rock\nstart: A\nmoves:"),
+        ParseRecordError::InvalidCard { line: 2 }
+    );
+    assert_eq!(
+        parse("hand A: Normal\nstart: A\nmoves:"),
+        ParseRecordError::MissingHand(Player::B)
+    );
+    assert_eq!(
+        parse("hand A: Normal\nhand B: Normal\nmoves:"),
+        ParseRecordError::MissingStartingPlayer
+    );
+    assert_eq!(
+        parse("hand A: Normal\nhand B: Normal\nstart: C\nmoves:"),
+        ParseRecordError::InvalidPlayer { line: 3 }
+    );
+    assert_eq!(
+        parse("hand A: Normal\nhand B: Normal\nstart: A\nseed: -1\nmoves:"),
+        ParseRecordError::InvalidSeed { line: 4 }
+    );
+    assert_eq!(
+        parse("hand A: Normal\nhand B: Normal\nstart: A"),
+        ParseRecordError::MissingMoves
+    );
+    assert_eq!(
+        parse("hand A: Normal\nhand B: Normal\nstart: A\nturn: B\nmoves:"),
+        ParseRecordError::UnknownHeader { line: 4 }
+    );
+    assert_eq!(
+        parse("hand A: Normal\nhand B: Normal\nstart: A\nmoves:\nP1@0,0"),
+        ParseRecordError::InvalidMove {
+            line: 5,
+            error: notation::ParseError::MissingInput,
+        }
+    );
+}
+
+#[test]
+fn replays_reach_the_same_states() {
+    for (record, hashes) in recorded_games() {
+        let mut replay = Replay::new(record);
+        assert_eq!(replay.len(), hashes.len() - 1);
+        assert_eq!(replay.state().hash(), hashes[0]);
+        assert!(replay.last_result().is_none());
+
+        for (position, &hash) in hashes.iter().enumerate().skip(1) {
+            assert!(!replay.is_finished());
+            assert!(replay.step_forward().unwrap().is_some());
+            assert_eq!(replay.position(), position);
+            assert_eq!(replay.state().hash(), hash);
+        }
+
+        assert!(replay.is_finished());
+        assert!(replay.step_forward().unwrap().is_none());
+    }
+}
+
+#[test]
+fn replays_step_back_and_seek() {
+    let (record, hashes) = recorded_games().swap_remove(0);
+    let mut replay = Replay::new(record);
+    assert!(replay.step_backward().is_none());
+
+    replay.seek(usize::MAX).unwrap();
+    assert_eq!(replay.position(), replay.len());
+    assert_eq!(replay.state().hash(), *hashes.last().unwrap());
+
+    let last = replay.last_result().cloned().unwrap();
+    assert_eq!(replay.step_backward().unwrap().events, last.events);
+    assert_eq!(replay.position(), replay.len() - 1);
+    assert_eq!(replay.state().hash(), hashes[replay.len() - 1]);
+
+    // stepping forward again gives the same result as before
+    assert_eq!(replay.step_forward().unwrap().unwrap().events, last.events);
+
+    for position in [3, 1, 0, 2] {
+        replay.seek(position).unwrap();
+        assert_eq!(replay.position(), position);
+        assert_eq!(replay.state().hash(), hashes[position]);
+    }
+}
+
+#[test]
+fn replays_stop_at_broken_moves() {
+    let text = "hand A: Normal\nhand B: Normal\nstart: A\nmoves:\nP0@-1,0<E\npush 5,5 E";
+    let mut replay = Replay::new(text.parse().unwrap());
+    assert_eq!(
+        replay.seek(2),
+        Err(ReplayError::NoCardAt { move_number: 1 })
+    );
+    assert_eq!(replay.position(), 1);
+
+    // player B pushing player A's card
+    let text = "hand A: Normal\nhand B: Normal\nstart: A\nmoves:\nP0@-1,0<E\npush 0,0 E";
+    let mut replay = Replay::new(text.parse().unwrap());
+    replay.step_forward().unwrap();
+    let card = replay
+        .state()
+        .card_at_position(common::position(0, 0))
+        .unwrap()
+        .1
+        .id;
+    assert_eq!(
+        replay.step_forward().unwrap_err(),
+        ReplayError::IllegalMove {
+            move_number: 1,
+            error: MoveError::NotOwned {
+                card,
+                owner: Some(Player::A),
+            },
+        }
+    );
+}
+
+#[test]
+fn cards_waiting_for_no_turns_start_available() {
+    let waiting = HeldCard::Waiting {
+        card: CardType::Normal,
+        turns_until_usable: 0,
+    };
+    let mut state = State::new(hand(), vec![waiting], Player::A, RuleSet::default());
+    let available = [HeldCard::Available(CardType::Normal)];
+    assert_eq!(state.player_hand(Player::B), available);
+    assert_eq!(
+        state.hash(),
+        State::new(hand(), available.to_vec(), Player::A, RuleSet::default()).hash()
+    );
+
+    // so the card is left alone by a move, and by undoing it
+    let m = state.enumerate_possible_moves().swap_remove(0);
+    let (_, undo) = state.execute_move_with_undo(&m);
+    assert_eq!(state.player_hand(Player::B), available);
+    state.undo_move(undo);
+    assert_eq!(state.player_hand(Player::B), available);
+}
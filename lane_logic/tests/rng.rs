@@ -0,0 +1,49 @@
+use lane_logic::{
+    card::CardType,
+    record::{GameRecord, ParseRecordError},
+    rng::{Pcg32, Rng},
+    rules::RuleSet,
+    HeldCard, Player,
+};
+
+#[test]
+fn pcg_matches_the_reference_implementation() {
+    // the first numbers from the PCG demo program, seeded with 42 on stream 54
+    let mut rng = Pcg32::new(42, 54);
+    let numbers: Vec<u32> = (0..6).map(|_| rng.next_u32()).collect();
+
+    assert_eq!(
+        numbers,
+        [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+    );
+}
+
+#[test]
+fn streams_give_different_numbers() {
+    let numbers = |stream| {
+        let mut rng = Pcg32::new(7, stream);
+        (0..4).map(|_| rng.next_u32()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(numbers(0), numbers(0));
+    assert_ne!(numbers(0), numbers(1));
+}
+
+#[test]
+fn records_keep_their_seed() {
+    let hand = vec![HeldCard::Available(CardType::Normal)];
+    let mut record = GameRecord::new(hand.clone(), hand, Player::A, RuleSet::default());
+    assert!(!record.to_string().contains("seed"));
+
+    record.seed = Some(u64::MAX);
+    let written = record.to_string();
+    assert!(written.contains(&format!("seed: {}", u64::MAX)));
+    assert_eq!(written.parse::<GameRecord>(), Ok(record));
+
+    assert_eq!(
+        written
+            .replace(&u64::MAX.to_string(), "many")
+            .parse::<GameRecord>(),
+        Err(ParseRecordError::InvalidSeed { line: 4 })
+    );
+}
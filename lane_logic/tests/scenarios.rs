@@ -0,0 +1,226 @@
+//! Runs every scenario in `tests/scenarios`. A scenario is a starting state
+//! drawn as in `lane_logic::diagram`, the moves to play from it and what should
+//! be true afterwards:
+//!
+//! ```text
+//! # comments start with a hash
+//! start:
+//!        -1   0   1
+//!    0    N (S) (S)
+//! turn: A
+//! hand A: Ghost
+//! hand B: Normal
+//! move: push -1,0 E
+//! board:
+//!         0   1   2
+//!    0    N (S) (S)
+//! score: 1 0
+//! captured:
+//! hand A: Ghost
+//! outcome: ongoing
+//! ```
+//!
+//! Every expectation is optional. `captured` lists where the captured cards
+//! were, `outcome` is one of `ongoing`, `A wins`, `B wins` or `draw`, and
+//! `illegal` gives the error the last move should fail with instead.
+
+use std::{fs, path::Path};
+
+use lane_logic::{notation::MoveNotation, GameOutcome, MoveResult, Player, State};
+
+/// Draws just the board, in the same way as a board after a move is drawn
+fn draw_board(drawing: &str) -> Result<String, String> {
+    let state: State = format!("{drawing}\nturn: A\nhand A:\nhand B:")
+        .parse()
+        .map_err(|error| format!("invalid board: {error}"))?;
+    Ok(state.board().to_string())
+}
+
+fn outcome_name(outcome: GameOutcome) -> &'static str {
+    match outcome {
+        GameOutcome::Ongoing => "ongoing",
+        GameOutcome::Win(Player::A) => "A wins",
+        GameOutcome::Win(Player::B) => "B wins",
+        GameOutcome::Draw(_) => "draw",
+    }
+}
+
+fn hand_line(state: &State, player: &str) -> String {
+    let prefix = format!("hand {player}:");
+    state
+        .to_string()
+        .lines()
+        .find(|line| line.starts_with(&prefix))
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn check(name: &str, expected: &str, actual: &str) -> Result<(), String> {
+    let normalise = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalise(expected) == normalise(actual) {
+        Ok(())
+    } else {
+        Err(format!("{name}: expected\n{expected}\nbut got\n{actual}"))
+    }
+}
+
+fn check_result(
+    state: &State,
+    result: &MoveResult,
+    expectation: &str,
+    value: &str,
+) -> Result<(), String> {
+    match expectation {
+        "board" => check("board", &draw_board(value)?, &state.board().to_string()),
+        "score" => check(
+            "score",
+            value,
+            &format!(
+                "{} {}",
+                result.score.player(Player::A),
+                result.score.player(Player::B)
+            ),
+        ),
+        "captured" => {
+            let mut captured: Vec<_> = result
+                .removed
+                .iter()
+                .filter(|(_, card)| {
+                    result.events.iter().any(|event| {
+                        matches!(event, lane_logic::BoardEvent::Captured { card: id, .. } if *id == card.id)
+                    })
+                })
+                .map(|(_, card)| (card.position.0.y, card.position.0.x))
+                .collect();
+            captured.sort();
+            let captured: Vec<_> = captured
+                .into_iter()
+                .map(|(y, x)| format!("{x},{y}"))
+                .collect();
+            check("captured", value, &captured.join(" "))
+        }
+        "hand A" | "hand B" => {
+            let player = &expectation[5..];
+            check(
+                expectation,
+                &format!("hand {player}: {value}"),
+                &hand_line(state, player),
+            )
+        }
+        "outcome" => check("outcome", value, outcome_name(result.outcome)),
+        _ => Err(format!("unknown expectation {expectation}")),
+    }
+}
+
+fn run(scenario: &str) -> Result<(), String> {
+    let mut start = String::new();
+    let mut moves = Vec::new();
+    let mut board: Option<String> = None;
+    let mut expectations = Vec::new();
+    let mut illegal = None;
+
+    for line in scenario.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let key_value = trimmed
+            .split_once(':')
+            .map(|(key, value)| (key.trim(), value.trim()));
+
+        match key_value {
+            Some(("start", _)) => {}
+            Some(("move", m)) => {
+                moves.push(
+                    m.parse::<MoveNotation>()
+                        .map_err(|error| format!("invalid move {m}: {error}"))?,
+                );
+            }
+            // the hands in the starting state are part of its drawing
+            Some(_) if moves.is_empty() => {
+                start.push_str(line);
+                start.push('\n');
+            }
+            Some(("board", _)) => board = Some(String::new()),
+            Some(("illegal", error)) => illegal = Some(error.to_string()),
+            Some((key, value)) => {
+                if let Some(board) = board.take() {
+                    expectations.push(("board".to_string(), board));
+                }
+                expectations.push((key.to_string(), value.to_string()));
+            }
+            None if moves.is_empty() => {
+                start.push_str(line);
+                start.push('\n');
+            }
+            None => match &mut board {
+                Some(board) => {
+                    board.push_str(line);
+                    board.push('\n');
+                }
+                None => return Err(format!("unexpected line {trimmed}")),
+            },
+        }
+    }
+
+    if let Some(board) = board {
+        expectations.push(("board".to_string(), board));
+    }
+
+    let mut state: State = start
+        .parse()
+        .map_err(|error| format!("invalid start: {error}"))?;
+    let (last, before) = moves.split_last().ok_or("no moves")?;
+
+    for (number, m) in before.iter().enumerate() {
+        let resolved = m
+            .resolve(&state)
+            .ok_or(format!("move {number}: no card there"))?;
+        state
+            .try_execute_move(&resolved)
+            .map_err(|error| format!("move {number}: {error}"))?;
+    }
+
+    let resolved = last.resolve(&state).ok_or("last move: no card there")?;
+    let result = state.try_execute_move(&resolved);
+
+    match (illegal, result) {
+        (Some(expected), Err(error)) => check("illegal", &expected, &error.to_string()),
+        (Some(expected), Ok(_)) => Err(format!("expected the move to fail with {expected}")),
+        (None, Err(error)) => Err(format!("last move: {error}")),
+        (None, Ok(result)) => expectations
+            .iter()
+            .try_for_each(|(key, value)| check_result(&state, &result, key, value)),
+    }
+}
+
+#[test]
+fn scenarios() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scenarios");
+    let mut paths: Vec<_> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "scenario"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    let failures: Vec<_> = paths
+        .iter()
+        .filter_map(|path| {
+            let scenario = fs::read_to_string(path).unwrap();
+            run(&scenario)
+                .err()
+                .map(|error| format!("{}: {error}", path.file_name().unwrap().to_string_lossy()))
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} scenarios failed:\n\n{}",
+        failures.len(),
+        paths.len(),
+        failures.join("\n\n")
+    );
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::{hand, play_random_games};
+use lane_logic::{card::CardType, rules::RuleSet, HeldCard, Move, MoveResult, Player, State};
+
+#[test]
+fn states_round_trip_through_json() {
+    play_random_games(
+        0x5e7d,
+        20,
+        40,
+        || State::new(hand(), hand(), Player::A, RuleSet::default()),
+        |state, m| {
+            let json = serde_json::to_string(&*state).unwrap();
+            let mut loaded: State = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+            assert_eq!(loaded.hash(), state.hash());
+
+            // in another order, as the loaded board gives its cards new indices
+            let moves = state.enumerate_possible_moves();
+            let loaded_moves = loaded.enumerate_possible_moves();
+            assert_eq!(loaded_moves.len(), moves.len());
+            assert!(moves.iter().all(|m| loaded_moves.contains(m)));
+
+            let result = state.execute_move(m);
+            loaded.execute_move(m);
+            assert_eq!(loaded.hash(), state.hash());
+
+            let json = serde_json::to_string(&result).unwrap();
+            let loaded_result: MoveResult = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&loaded_result).unwrap(), json);
+            result
+        },
+    );
+}
+
+#[test]
+fn moves_refer_to_the_same_cards_after_loading() {
+    let state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    let loaded: State = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+
+    for (_, card) in state.board_state() {
+        assert_eq!(
+            loaded.card_by_id(card.id).unwrap().1.position,
+            card.position
+        );
+    }
+
+    for m in state.enumerate_possible_moves() {
+        let json = serde_json::to_string(&m).unwrap();
+        let m: Move = serde_json::from_str(&json).unwrap();
+        assert!(loaded.can_execute_move(&m));
+    }
+}
+
+#[test]
+fn overlapping_cards_are_rejected() {
+    let state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    let mut json: serde_json::Value = serde_json::to_value(&state).unwrap();
+
+    json["cards"][1]["position"] = json["cards"][0]["position"].clone();
+
+    assert!(serde_json::from_value::<State>(json).is_err());
+}
+
+#[test]
+fn ids_which_were_never_handed_out_are_rejected() {
+    let state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    let json: serde_json::Value = serde_json::to_value(&state).unwrap();
+
+    for id in [json["next_card_id"].clone(), 3_000_000_000u32.into()] {
+        let mut json = json.clone();
+        json["cards"][0]["id"] = id;
+        assert!(serde_json::from_value::<State>(json).is_err());
+    }
+}
+
+#[test]
+fn states_with_cards_taken_off_the_board_round_trip() {
+    let mut state = State::new(hand(), hand(), Player::A, RuleSet::default());
+
+    // put two cards down and pick one up again, leaving a gap on the board
+    for _ in 0..2 {
+        let m = state
+            .enumerate_possible_moves()
+            .into_iter()
+            .find(|m| matches!(m, Move::PlaceCard(_)))
+            .unwrap();
+        state.execute_move(&m);
+    }
+    let pick = state
+        .enumerate_possible_moves()
+        .into_iter()
+        .find(|m| matches!(m, Move::PickCard(_)))
+        .unwrap();
+    state.execute_move(&pick);
+
+    let json = serde_json::to_string(&state).unwrap();
+    let loaded: State = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    assert_eq!(loaded.hash(), state.hash());
+    for (_, card) in state.board_state() {
+        assert_eq!(
+            loaded.card_by_id(card.id).unwrap().1.position,
+            card.position
+        );
+    }
+}
+
+#[test]
+fn waiting_cards_with_no_turns_left_are_rejected() {
+    let state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    let mut json: serde_json::Value = serde_json::to_value(&state).unwrap();
+
+    json["hands"][0][0] = serde_json::json!({
+        "Waiting": { "card": "Normal", "turns_until_usable": 0 }
+    });
+    assert!(serde_json::from_value::<State>(json.clone()).is_err());
+
+    json["hands"][0][0]["Waiting"]["turns_until_usable"] = 1.into();
+    let loaded: State = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        loaded.player_hand(Player::A)[0],
+        HeldCard::Waiting {
+            card: CardType::Normal,
+            turns_until_usable: 1,
+        }
+    );
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::{hand, id_at, play_random_games, position};
+use lane_logic::{
+    card::CardType, rules::RuleSet, Direction, Move, MoveError, Player, PushCardMove, State,
+};
+
+#[test]
+fn simulating_matches_executing() {
+    play_random_games(
+        0x5103,
+        30,
+        40,
+        || State::new(hand(), hand(), Player::A, RuleSet::default()),
+        |state, m| {
+            let before = format!("{state:?}");
+            let simulated = state.simulate(m).unwrap();
+            assert_eq!(format!("{state:?}"), before, "simulating changed the state");
+
+            let result = state.try_execute_move(m).unwrap();
+            assert_eq!(format!("{simulated:?}"), format!("{result:?}"));
+            result
+        },
+    );
+}
+
+#[test]
+fn illegal_moves_fail_to_simulate() {
+    let mut rules = RuleSet::default();
+    rules.starting_layout.extend([
+        (position(-1, 0), CardType::Normal, Some(Player::A)),
+        (position(-2, 0), CardType::Block, None),
+        (position(0, 1), CardType::Normal, Some(Player::B)),
+    ]);
+    let state = State::new(hand(), hand(), Player::A, rules);
+
+    let own = id_at(&state, -1, 0);
+    let theirs = id_at(&state, 0, 1);
+
+    let into_block = Move::PushCard(PushCardMove {
+        place: own,
+        direction: Direction::West,
+    });
+    let their_card = Move::PushCard(PushCardMove {
+        place: theirs,
+        direction: Direction::North,
+    });
+
+    for m in [into_block, their_card] {
+        let error = state.simulate(&m).unwrap_err();
+        assert_eq!(state.check_move(&m), Err(error));
+        assert!(!state.can_execute_move(&m));
+    }
+}
+
+#[test]
+fn pushes_are_legal_if_they_push_something() {
+    let mut rules = RuleSet::default();
+    rules.starting_layout.extend([
+        (position(-1, 0), CardType::Normal, Some(Player::A)),
+        (position(1, -1), CardType::Redirect, Some(Player::A)),
+    ]);
+    let state = State::new(hand(), hand(), Player::A, rules);
+
+    let normal = id_at(&state, -1, 0);
+    let redirect = id_at(&state, 1, -1);
+    let push = |place, direction| Move::PushCard(PushCardMove { place, direction });
+
+    // the whole row moves along, however many cards are in it
+    let into_score_cards = state.simulate(&push(normal, Direction::East)).unwrap();
+    assert_eq!(into_score_cards.moved.len(), 3);
+
+    // nothing in front, but the score card beside it is pushed out of the way
+    let sideways = state.simulate(&push(redirect, Direction::East)).unwrap();
+    assert_eq!(sideways.moved.len(), 2);
+
+    assert_eq!(
+        state.check_move(&push(normal, Direction::North)),
+        Err(MoveError::NothingToPush {
+            card: normal,
+            direction: Direction::North,
+        })
+    );
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{hand, play_random_games};
+use lane_logic::{rules::RuleSet, CardId, HeldCard, Index, Move, Player, Position, State};
+
+/// Everything undoing a move has to put back as it was
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    turn: Player,
+    hands: [Vec<HeldCard>; 2],
+    hash: u64,
+    turns_played: usize,
+    repetitions: usize,
+    cards: Vec<(Index, CardId, Position, &'static str, Option<Player>)>,
+}
+
+impl Snapshot {
+    fn of(state: &State) -> Self {
+        let mut cards: Vec<_> = state
+            .board_state()
+            .map(|(idx, card)| {
+                (
+                    idx,
+                    card.id,
+                    card.position,
+                    card.card.to_type().name(),
+                    card.belonging_player,
+                )
+            })
+            .collect();
+        cards.sort_by_key(|&(idx, ..)| idx);
+
+        Snapshot {
+            turn: state.turn(),
+            hands: [
+                state.player_hand(Player::A).to_vec(),
+                state.player_hand(Player::B).to_vec(),
+            ],
+            hash: state.hash(),
+            turns_played: state.turns_played(),
+            repetitions: state.repetitions(),
+            cards,
+        }
+    }
+}
+
+#[test]
+fn undoing_puts_everything_back() {
+    // a long cooldown so hands have waiting cards counting down
+    let rules = RuleSet {
+        default_cooldown: 3,
+        ..RuleSet::default()
+    };
+
+    play_random_games(
+        0x0d0,
+        30,
+        40,
+        || State::new(hand(), hand(), Player::A, rules.clone()),
+        |state, m| {
+            let before = Snapshot::of(state);
+
+            for m in state.enumerate_possible_moves() {
+                let (_, undo) = state.execute_move_with_undo(&m);
+                state.undo_move(undo);
+                assert_eq!(Snapshot::of(state), before, "undoing {m:?}");
+            }
+
+            state.execute_move(m)
+        },
+    );
+}
+
+#[test]
+fn undoing_a_pick_returns_the_card_and_its_id() {
+    let mut state = State::new(hand(), hand(), Player::A, RuleSet::default());
+    for _ in 0..2 {
+        let m = state.enumerate_possible_moves().swap_remove(0);
+        state.execute_move(&m);
+    }
+
+    let pick = state
+        .enumerate_possible_moves()
+        .into_iter()
+        .find(|m| matches!(m, Move::PickCard(_)))
+        .unwrap();
+    let before = Snapshot::of(&state);
+
+    let (result, undo) = state.execute_move_with_undo(&pick);
+    let (id, _) = &result.removed[0];
+    assert!(state.card_by_id(*id).is_none());
+
+    state.undo_move(undo);
+    assert_eq!(Snapshot::of(&state), before);
+    assert!(state.card_by_id(*id).is_some());
+}
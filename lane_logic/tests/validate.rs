@@ -0,0 +1,128 @@
+mod common;
+
+use common::{hand, play_random_games, position};
+use lane_logic::{
+    card::CardType,
+    rules::RuleSet,
+    validate::{ValidationError, ValidationReport},
+    CardId, Direction, HeldCard, HeldCardIndex, Move, MoveError, PlaceCardMove, Player, Position,
+    State,
+};
+
+fn rules_with(layout: &[(Position, CardType, Option<Player>)]) -> RuleSet {
+    let mut rules = RuleSet::default();
+    rules.starting_layout.extend_from_slice(layout);
+    rules
+}
+
+#[test]
+fn games_stay_valid() {
+    for connected in [false, true] {
+        let rules = RuleSet {
+            connected,
+            ..RuleSet::default()
+        };
+
+        play_random_games(
+            0x7a1d,
+            20,
+            40,
+            || State::new(hand(), hand(), Player::A, rules.clone()),
+            |state, m| {
+                assert_eq!(state.validate(), Ok(()));
+                let result = state.execute_move(m);
+                assert_eq!(state.validate(), Ok(()));
+                result
+            },
+        );
+    }
+}
+
+#[test]
+fn broken_states_are_reported() {
+    let state = State::new(
+        vec![HeldCard::Waiting {
+            card: CardType::Normal,
+            turns_until_usable: 0,
+        }],
+        hand(),
+        Player::A,
+        RuleSet {
+            connected: true,
+            ..rules_with(&[
+                (position(0, 0), CardType::Normal, Some(Player::B)),
+                (position(5, 5), CardType::Score, Some(Player::A)),
+            ])
+        },
+    );
+
+    assert_eq!(
+        state.validate(),
+        Err(ValidationReport {
+            errors: vec![
+                ValidationError::Overlapping {
+                    position: position(0, 0),
+                    cards: [CardId(0), CardId(2)],
+                },
+                ValidationError::OwnedScoreCard {
+                    card: CardId(3),
+                    owner: Player::A,
+                },
+                ValidationError::Disconnected,
+            ]
+        })
+    );
+}
+
+#[test]
+fn moves_which_split_the_board_need_the_rule_to_be_off() {
+    // placing east of (3, 1) moves that card under (2, 0), capturing it and
+    // leaving (2, -1) on its own
+    let mut rules = rules_with(&[
+        (position(2, 0), CardType::Normal, Some(Player::B)),
+        (position(2, -1), CardType::Normal, Some(Player::A)),
+        (position(3, 0), CardType::Normal, Some(Player::A)),
+        (position(3, 1), CardType::Normal, Some(Player::A)),
+    ]);
+    let m = Move::PlaceCard(PlaceCardMove {
+        direction: Direction::West,
+        coordinate: position(4, 1),
+        card: HeldCardIndex(1),
+    });
+
+    let mut state = State::new(hand(), hand(), Player::A, rules.clone());
+    let result = state.try_execute_move(&m).unwrap();
+    assert_eq!(result.removed.len(), 1);
+
+    rules.connected = true;
+    let state = State::new(hand(), hand(), Player::A, rules);
+    assert_eq!(state.simulate(&m).unwrap_err(), MoveError::DisconnectsBoard);
+    assert!(!state.enumerate_possible_moves().contains(&m));
+}
+
+#[test]
+fn the_connected_rule_can_be_written_down() {
+    let rules = RuleSet {
+        connected: true,
+        ..RuleSet::default()
+    };
+    let written = rules.to_string();
+
+    assert!(written.contains("connected=yes"));
+    assert_eq!(written.parse::<RuleSet>(), Ok(rules));
+    assert!(!RuleSet::default().to_string().contains("connected"));
+}
+
+#[cfg(feature = "validate")]
+#[test]
+#[should_panic(expected = "the state is invalid")]
+fn moves_from_broken_states_panic() {
+    let mut state = State::new(
+        hand(),
+        hand(),
+        Player::A,
+        rules_with(&[(position(1, 0), CardType::Normal, Some(Player::A))]),
+    );
+    let m = state.enumerate_possible_moves().remove(0);
+    state.execute_move(&m);
+}